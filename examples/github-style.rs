@@ -1,6 +1,6 @@
 extern crate term;
 extern crate difference;
-use difference::{Difference, Changeset};
+use difference::{Difference, Changeset, Refined, Refinement};
 use std::io::Write;
 
 // Screenshot:
@@ -18,53 +18,64 @@ fn main() {
                  just for you.\n\
                  (It's quite true).";
 
-    let Changeset { diffs, .. } = Changeset::new(text1, text2, "\n");
+    // Diff by lines, then diff each replaced line again by words.
+    let Refined { diffs, .. } = Changeset::new(text1, text2, "\n").refine(" ");
 
     let mut t = term::stdout().unwrap();
 
-    for i in 0..diffs.len() {
-        match diffs[i] {
-            Difference::Same(ref x) => {
+    for d in &diffs {
+        match *d {
+            Refinement::Single(Difference::Same(ref x)) => {
                 t.reset().unwrap();
                 writeln!(t, " {}", x);
             }
-            Difference::Add(ref x) => {
-                match diffs[i - 1] {
-                    Difference::Rem(ref y) => {
-                        t.fg(term::color::GREEN).unwrap();
-                        write!(t, "+");
-                        let Changeset { diffs, .. } = Changeset::new(y, x, " ");
-                        for c in diffs {
-                            match c {
-                                Difference::Same(ref z) => {
-                                    t.fg(term::color::GREEN).unwrap();
-                                    write!(t, "{}", z);
-                                    write!(t, " ");
-                                }
-                                Difference::Add(ref z) => {
-                                    t.fg(term::color::WHITE).unwrap();
-                                    t.bg(term::color::GREEN).unwrap();
-                                    write!(t, "{}", z);
-                                    t.reset().unwrap();
-                                    write!(t, " ");
-                                }
-                                _ => (),
-                            }
-                        }
-                        writeln!(t, "");
-                    }
-                    _ => {
-                        t.fg(term::color::BRIGHT_GREEN).unwrap();
-                        writeln!(t, "+{}", x);
-                    }
-                };
+            Refinement::Single(Difference::Add(ref x)) => {
+                t.fg(term::color::BRIGHT_GREEN).unwrap();
+                writeln!(t, "+{}", x);
             }
-            Difference::Rem(ref x) => {
+            Refinement::Single(Difference::Rem(ref x)) => {
                 t.fg(term::color::RED).unwrap();
                 writeln!(t, "-{}", x);
             }
+            Refinement::Replace(Changeset { ref diffs, .. }) => {
+                t.fg(term::color::RED).unwrap();
+                writeln!(t, "-{}", text_before(diffs));
+
+                t.fg(term::color::GREEN).unwrap();
+                write!(t, "+");
+                for c in diffs {
+                    match *c {
+                        Difference::Same(ref z) => {
+                            t.fg(term::color::GREEN).unwrap();
+                            write!(t, "{}", z);
+                            write!(t, " ");
+                        }
+                        Difference::Add(ref z) => {
+                            t.fg(term::color::WHITE).unwrap();
+                            t.bg(term::color::GREEN).unwrap();
+                            write!(t, "{}", z);
+                            t.reset().unwrap();
+                            write!(t, " ");
+                        }
+                        _ => (),
+                    }
+                }
+                writeln!(t);
+            }
         }
     }
     t.reset().unwrap();
     t.flush().unwrap();
 }
+
+// the removed line of a word-level replacement
+fn text_before(diffs: &[Difference]) -> String {
+    let words: Vec<&str> = diffs
+        .iter()
+        .filter_map(|d| match *d {
+            Difference::Same(ref x) | Difference::Rem(ref x) => Some(x.as_str()),
+            Difference::Add(_) => None,
+        })
+        .collect();
+    words.join(" ")
+}
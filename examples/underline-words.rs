@@ -32,7 +32,7 @@ fn main() {
     }
     t.reset().unwrap();
 
-    writeln!(t, "");
+    writeln!(t);
 
     for c in &diffs {
         match *c {
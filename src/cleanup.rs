@@ -0,0 +1,233 @@
+use super::{join, saturate, Changeset, Kind};
+
+#[cfg(test)]
+use super::Difference;
+
+// a run of unchanged tokens, or the tokens removed and added between two such runs
+enum Group<'a> {
+    Same(Vec<&'a str>),
+    Change(Vec<&'a str>, Vec<&'a str>),
+}
+
+impl Changeset {
+    /// Absorbs short coincidental equalities into the changes around them,
+    /// like `diff_cleanupSemantic` of diff-match-patch.
+    ///
+    /// Character level changesets of rewritten text often consist of many
+    /// small changes, interleaved with single unchanged characters that happen
+    /// to appear in both texts. An unchanged run is turned into a removal and an
+    /// addition, if it is no longer than the changes on either side of it.
+    /// This makes the changeset easier to read, but increases its edit distance
+    /// by twice the number of absorbed tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference};
+    ///
+    /// let mut changeset = Changeset::new("mouse", "sofas", "");
+    /// changeset.cleanup_semantic();
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Rem("mouse".to_string()),
+    ///     Difference::Add("sofas".to_string()),
+    /// ]);
+    /// assert_eq!(changeset.distance, 10);
+    /// ```
+    pub fn cleanup_semantic(&mut self) {
+        self.cleanup(|len, before, after| {
+            len <= before.0.max(before.1) && len <= after.0.max(after.1)
+        })
+    }
+
+    /// Absorbs unchanged runs shorter than `edit_cost` characters into the
+    /// changes around them, like `diff_cleanupEfficiency` of diff-match-patch.
+    ///
+    /// `edit_cost` is the cost of an additional change in characters, so this
+    /// reduces the number of changes for output that is meant to be processed
+    /// rather than read, e.g. a patch. An unchanged run is absorbed if it is
+    /// surrounded by removals and additions on both sides, or by three of them
+    /// and shorter than half of `edit_cost`. Like `Changeset::cleanup_semantic`,
+    /// this updates the edit distance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference};
+    ///
+    /// let mut changeset = Changeset::new("abxcd", "12x34", "");
+    /// changeset.cleanup_efficiency(4);
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Rem("abxcd".to_string()),
+    ///     Difference::Add("12x34".to_string()),
+    /// ]);
+    /// ```
+    pub fn cleanup_efficiency(&mut self, edit_cost: usize) {
+        self.cleanup(|len, before, after| {
+            let sides = [before.0 > 0, before.1 > 0, after.0 > 0, after.1 > 0];
+            let count = sides.iter().filter(|&&x| x).count();
+            len < edit_cost && (count == 4 || (count == 3 && len * 2 < edit_cost))
+        })
+    }
+
+    // merges every unchanged run, for which `absorb` returns true, into the
+    // changes before and after it, until there are no such runs left
+    //
+    // `absorb` is called with the length of the unchanged run in characters, and
+    // the lengths of the removed and added text before and after it
+    fn cleanup<F>(&mut self, absorb: F)
+    where
+        F: Fn(usize, (usize, usize), (usize, usize)) -> bool,
+    {
+        let tokens = self.kinds();
+        let mut groups = groups(&tokens);
+        let len = |x: &[&str]| text_len(x, &self.split);
+
+        let mut absorbed = 0;
+        let mut i = 1;
+        while i + 1 < groups.len() {
+            let merged = match (&groups[i - 1], &groups[i], &groups[i + 1]) {
+                (Group::Change(rb, ab), Group::Same(x), Group::Change(ra, aa))
+                    if absorb(len(x), (len(rb), len(ab)), (len(ra), len(aa))) =>
+                {
+                    absorbed += x.len();
+                    let rem = [&rb[..], x, ra].concat();
+                    let add = [&ab[..], x, aa].concat();
+                    Some(Group::Change(rem, add))
+                }
+                _ => None,
+            };
+            match merged {
+                Some(group) => {
+                    groups.splice(i - 1..i + 2, Some(group));
+                    // the grown change may allow absorbing the run before it
+                    i = if i > 2 { i - 2 } else { 1 };
+                }
+                None => i += 1,
+            }
+        }
+        if absorbed == 0 {
+            return;
+        }
+
+        let mut tokens = Vec::new();
+        for group in groups {
+            match group {
+                Group::Same(x) => tokens.extend(x.into_iter().map(|x| (Kind::Same, x))),
+                Group::Change(rem, add) => {
+                    tokens.extend(rem.into_iter().map(|x| (Kind::Rem, x)));
+                    tokens.extend(add.into_iter().map(|x| (Kind::Add, x)));
+                }
+            }
+        }
+        let (diffs, bounds) = join(&tokens, &self.split, self.bounds.is_some());
+        self.diffs = diffs;
+        self.bounds = bounds;
+        // every absorbed token is now removed and added
+        self.distance = self.distance.saturating_add(saturate(absorbed).saturating_mul(2));
+    }
+}
+
+// groups the removed and added tokens between runs of unchanged tokens
+fn groups<'a>(tokens: &[(Kind, &'a str)]) -> Vec<Group<'a>> {
+    let mut ret = Vec::new();
+    for &(kind, x) in tokens {
+        match (kind, ret.last_mut()) {
+            (Kind::Same, Some(Group::Same(same))) => same.push(x),
+            (Kind::Same, _) => ret.push(Group::Same(vec![x])),
+            (Kind::Rem, Some(Group::Change(rem, add))) if add.is_empty() => rem.push(x),
+            (Kind::Add, Some(Group::Change(_, add))) => add.push(x),
+            (Kind::Rem, _) => ret.push(Group::Change(vec![x], Vec::new())),
+            (Kind::Add, _) => ret.push(Group::Change(Vec::new(), vec![x])),
+        }
+    }
+    ret
+}
+
+// the length in characters of tokens joined by `split`
+fn text_len(tokens: &[&str], split: &str) -> usize {
+    let chars: usize = tokens.iter().map(|x| x.chars().count()).sum();
+    chars + tokens.len().saturating_sub(1) * split.chars().count()
+}
+
+#[test]
+fn test_cleanup_semantic() {
+    let mut changeset = Changeset::new("I like the blue car", "We liked a blue cart", "");
+    changeset.cleanup_semantic();
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Rem("I".to_string()),
+            Difference::Add("We".to_string()),
+            Difference::Same(" like".to_string()),
+            Difference::Rem(" the".to_string()),
+            Difference::Add("d a".to_string()),
+            Difference::Same(" blue car".to_string()),
+            Difference::Add("t".to_string()),
+        ]
+    );
+    assert_eq!(changeset.distance, 11);
+
+    // equalities absorbed later may allow absorbing earlier ones
+    let mut changeset = Changeset::new("The cat sat", "A dog sits", "");
+    changeset.cleanup_semantic();
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Rem("The cat sat".to_string()),
+            Difference::Add("A dog sits".to_string()),
+        ]
+    );
+
+    // long enough equalities are kept
+    let mut changeset = Changeset::new("a quick fox", "the quick dog", " ");
+    let before = changeset.clone();
+    changeset.cleanup_semantic();
+    assert_eq!(changeset, before);
+}
+
+#[test]
+fn test_cleanup_efficiency() {
+    // surrounded by three changes, but not shorter than half the edit cost
+    let mut changeset = Changeset::new("abxyc", "12xyc", "");
+    let before = changeset.clone();
+    changeset.cleanup_efficiency(4);
+    assert_eq!(changeset, before);
+
+    let mut changeset = Changeset::new("a b x c d", "e f x g", " ");
+    changeset.cleanup_efficiency(4);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Rem("a b x c d".to_string()),
+            Difference::Add("e f x g".to_string()),
+        ]
+    );
+    assert_eq!(changeset.distance, 9);
+}
+
+#[test]
+fn test_cleanup_lines() {
+    use tokenize::Lines;
+
+    // nothing to absorb, so nothing changes
+    let mut changeset = Changeset::with_tokenizer("a\nb\n", "a\nc\n", &Lines);
+    let before = changeset.clone();
+    changeset.cleanup_semantic();
+    assert_eq!(changeset, before);
+    assert_eq!(changeset.edit_distance(), 2);
+
+    // the distance counts lines, not characters
+    let mut changeset = Changeset::with_tokenizer("a\nx\nb\n", "c\nx\nd\n", &Lines);
+    changeset.cleanup_semantic();
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Rem("a\nx\nb\n".to_string()),
+            Difference::Add("c\nx\nd\n".to_string()),
+        ]
+    );
+    assert_eq!(changeset.distance, 6);
+    assert_eq!(changeset.edit_distance(), 6);
+}
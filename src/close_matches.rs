@@ -0,0 +1,107 @@
+use lcs::{lcs, Op};
+use std::cmp::min;
+use std::collections::HashMap;
+
+#[cfg(test)]
+use super::Changeset;
+
+/// Finds the candidates that are most similar to `word`, like
+/// `get_close_matches` of Python's `difflib`, e.g. for "did you mean"
+/// suggestions.
+///
+/// Returns at most `n` candidates, whose similarity to `word` is at least
+/// `cutoff`, the most similar first. Candidates that are equally similar
+/// keep their order. The similarity is the `ratio` of `Changeset::stats`
+/// for a character level changeset, from `0.0` to `1.0`.
+///
+/// Candidates are prefiltered by cheap upper bounds of their similarity,
+/// computed from their length and their characters, so only a few have to be
+/// diffed, even if there are thousands of them.
+///
+/// # Examples
+///
+/// ```
+/// use difference::close_matches;
+///
+/// let commands = ["build", "check", "clean", "clippy", "test"];
+/// assert_eq!(close_matches("chek", &commands, 3, 0.6), vec!["check"]);
+/// assert_eq!(close_matches("cleen", &commands, 3, 0.6), vec!["clean"]);
+/// assert!(close_matches("deploy", &commands, 3, 0.6).is_empty());
+/// ```
+pub fn close_matches<'a, I, S>(word: &str, candidates: I, n: usize, cutoff: f64) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a S>,
+    S: AsRef<str> + ?Sized + 'a,
+{
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let a: Vec<char> = word.chars().collect();
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for &c in &a {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+
+    let mut matches: Vec<(f64, &'a str)> = Vec::new();
+    for candidate in candidates {
+        let candidate = candidate.as_ref();
+        let len = candidate.chars().count();
+        if ratio(min(a.len(), len), a.len() + len) < cutoff {
+            continue;
+        }
+
+        // the characters both strings have in common, regardless of their order
+        let mut available = counts.clone();
+        let mut common = 0;
+        for c in candidate.chars() {
+            if let Some(x) = available.get_mut(&c) {
+                if *x > 0 {
+                    *x -= 1;
+                    common += 1;
+                }
+            }
+        }
+        if ratio(common, a.len() + len) < cutoff {
+            continue;
+        }
+
+        let b: Vec<char> = candidate.chars().collect();
+        let same = lcs(&a, &b)
+            .iter()
+            .filter(|op| matches!(op, Op::Same(_, _)))
+            .count();
+        let score = ratio(same, a.len() + len);
+        if score >= cutoff {
+            matches.push((score, candidate));
+        }
+    }
+
+    // sort_by is stable, so equally similar candidates keep their order
+    matches.sort_by(|x, y| y.0.partial_cmp(&x.0).unwrap());
+    matches.truncate(n);
+    matches.into_iter().map(|x| x.1).collect()
+}
+
+// twice the number of matching characters divided by their total number
+fn ratio(matching: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        2.0 * matching as f64 / total as f64
+    }
+}
+
+#[test]
+fn test_close_matches() {
+    let words = vec!["ape".to_string(), "apple".to_string(), "peach".to_string(), "puppy".to_string()];
+    assert_eq!(close_matches("appel", &words, 3, 0.6), vec!["apple", "ape"]);
+    assert_eq!(close_matches("appel", &words, 1, 0.6), vec!["apple"]);
+    assert_eq!(close_matches("appel", &words, 0, 0.6), Vec::<&str>::new());
+    assert_eq!(close_matches("", &["", "a"], 3, 0.0), vec!["", "a"]);
+
+    // the similarity is the ratio of a character level changeset
+    let ratio = Changeset::new("appel", "ape", "").stats().ratio;
+    assert_eq!(close_matches("appel", &words, 3, ratio), vec!["apple", "ape"]);
+    assert_eq!(close_matches("appel", &words, 3, ratio + 0.01), vec!["apple"]);
+}
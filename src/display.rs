@@ -1,20 +1,222 @@
-
-
 use super::{Changeset, Difference};
+use moved::Move;
+use style::Theme;
+use std::borrow::Cow;
+use std::env;
+use std::ffi::OsString;
 use std::fmt;
+use std::io::{self, IsTerminal};
 
-impl fmt::Display for Changeset {
+/// A `Changeset` rendered with a custom `Theme`, created by `Changeset::styled`
+pub struct Styled<'a> {
+    changeset: &'a Changeset,
+    theme: &'a Theme,
+    whitespace: bool,
+    moves: Vec<Move>,
+}
+
+impl Changeset {
+    /// Renders the changeset for the terminal, using the styles of `theme`
+    /// instead of the default colors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Theme};
+    ///
+    /// let changeset = Changeset::new("Diffs are awesome", "Diffs are cool", " ");
+    /// println!("{}", changeset.styled(&Theme::colorblind()));
+    /// ```
+    pub fn styled<'a>(&'a self, theme: &'a Theme) -> Styled<'a> {
+        Styled {
+            changeset: self,
+            theme,
+            whitespace: false,
+            moves: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Styled<'a> {
+    /// Makes whitespace in added and removed text visible, see `Plain::show_whitespace`.
+    pub fn show_whitespace(mut self) -> Styled<'a> {
+        self.whitespace = true;
+        self
+    }
+
+    /// Renders blocks that were moved with the `moved_add` and `moved_rem`
+    /// styles of the theme, like `git diff --color-moved`.
+    /// Moves are detected by `Changeset::moves` with a `min_similarity` of `1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Theme};
+    ///
+    /// let changeset = Changeset::new(
+    ///     "let message = String::from(\"hello\");\nfn main() {\n}",
+    ///     "fn main() {\n}\nlet message = String::from(\"hello\");",
+    ///     "\n",
+    /// );
+    /// println!("{}", changeset.styled(&Theme::default()).color_moved());
+    /// ```
+    pub fn color_moved(mut self) -> Styled<'a> {
+        self.moves = self.changeset.moves(1.0);
+        self
+    }
+}
+
+impl<'a> fmt::Display for Styled<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for d in &self.diffs {
-            match *d {
-                Difference::Same(ref x) => {
-                    try!(write!(f, "{}{}", x, self.split));
+        for (i, d) in self.changeset.diffs.iter().enumerate() {
+            let moved = |pick: fn(&Move) -> usize| self.moves.iter().any(|m| pick(m) == i);
+            let style = match *d {
+                Difference::Same(_) => &self.theme.same,
+                Difference::Add(_) if moved(|m| m.add) => &self.theme.moved_add,
+                Difference::Rem(_) if moved(|m| m.rem) => &self.theme.moved_rem,
+                Difference::Add(_) => &self.theme.add,
+                Difference::Rem(_) => &self.theme.rem,
+            };
+            let x = changed_text(self.changeset, i, self.whitespace);
+            write!(f, "{}{}", style.paint(&x), self.changeset.split)?;
+        }
+        Ok(())
+    }
+}
+
+// returns the text of the `i`th difference, with whitespace made visible
+// if it is a change and `whitespace` is set
+fn changed_text<'a>(changeset: &'a Changeset, i: usize, whitespace: bool) -> Cow<'a, str> {
+    let x = match changeset.diffs[i] {
+        Difference::Same(ref x) => return Cow::Borrowed(x),
+        Difference::Add(ref x) | Difference::Rem(ref x) => x,
+    };
+    if !whitespace {
+        return Cow::Borrowed(x);
+    }
+
+    let mut ret = visible(x);
+    // a change of the last line of a line-split changeset that ends empty
+    // means that a final line break was added or removed
+    let split = &changeset.split;
+    if i + 1 == changeset.diffs.len() && split.contains('\n') &&
+        (x.is_empty() || x.ends_with(split.as_str()))
+    {
+        ret.push('⏎');
+    }
+    Cow::Owned(ret)
+}
+
+// shows spaces, tabs and carriage returns in `x` as visible characters
+fn visible(x: &str) -> String {
+    x.chars()
+        .map(|c| match c {
+            ' ' => '·',
+            '\t' => '→',
+            '\r' => '␍',
+            c => c,
+        })
+        .collect()
+}
+
+/// A `Changeset` rendered without colors, created by `Changeset::plain`
+pub struct Plain<'a> {
+    changeset: &'a Changeset,
+    whitespace: bool,
+}
+
+impl Changeset {
+    /// Renders the changeset as plain text, marking changes with characters
+    /// instead of terminal colors.
+    ///
+    /// Line-level changesets (split by `"\n"` or tokenized by `Lines`) are printed
+    /// like a unified diff, with every line prefixed by `+`, `-` or a space.
+    /// All other changesets are printed inline, wrapping removals in `[-...-]`
+    /// and additions in `{+...+}`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::Changeset;
+    ///
+    /// let changeset = Changeset::new("Diffs are awesome", "Diffs are cool", " ");
+    /// assert_eq!(changeset.plain().to_string(), "Diffs are [-awesome-] {+cool+} ");
+    ///
+    /// let changeset = Changeset::new("a\nb", "a\nc", "\n");
+    /// assert_eq!(changeset.plain().to_string(), " a\n-b\n+c\n");
+    /// ```
+    pub fn plain<'a>(&'a self) -> Plain<'a> {
+        Plain {
+            changeset: self,
+            whitespace: false,
+        }
+    }
+}
+
+impl<'a> Plain<'a> {
+    /// Makes whitespace in added and removed text visible, by showing spaces
+    /// as `·`, tabs as `→` and carriage returns as `␍`. An added or removed
+    /// line break at the end of a line-split changeset is shown as `⏎`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::Changeset;
+    ///
+    /// let changeset = Changeset::new("a\nb", "a \nb\n", "\n");
+    /// assert_eq!(
+    ///     changeset.plain().show_whitespace().to_string(),
+    ///     "-a\n+a·\n b\n+⏎\n"
+    /// );
+    /// ```
+    pub fn show_whitespace(mut self) -> Plain<'a> {
+        self.whitespace = true;
+        self
+    }
+}
+
+impl<'a> fmt::Display for Plain<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let split = &self.changeset.split;
+        if self.changeset.lines {
+            // the terminators are taken from the text, so lines are split
+            // before making whitespace visible
+            for (i, d) in self.changeset.diffs.iter().enumerate() {
+                let (prefix, x) = match *d {
+                    Difference::Same(ref x) => (" ", x),
+                    Difference::Add(ref x) => ("+", x),
+                    Difference::Rem(ref x) => ("-", x),
+                };
+                for line in self.changeset.split_lines(i) {
+                    if self.whitespace && prefix != " " {
+                        writeln!(f, "{}{}", prefix, visible(line))?;
+                    } else {
+                        writeln!(f, "{}{}", prefix, line)?;
+                    }
+                }
+                if !x.ends_with('\n') && !x.ends_with('\r') {
+                    writeln!(f, "\\ No newline at end of file")?;
                 }
-                Difference::Add(ref x) => {
-                    try!(write!(f, "\x1b[92m{}\x1b[0m{}", x, self.split));
+            }
+        } else if split.contains('\n') {
+            for (i, d) in self.changeset.diffs.iter().enumerate() {
+                let prefix = match *d {
+                    Difference::Same(_) => " ",
+                    Difference::Add(_) => "+",
+                    Difference::Rem(_) => "-",
+                };
+                let x = changed_text(self.changeset, i, self.whitespace);
+                for line in x.split(split.as_str()) {
+                    write!(f, "{}{}{}", prefix, line, split)?;
                 }
-                Difference::Rem(ref x) => {
-                    try!(write!(f, "\x1b[91m{}\x1b[0m{}", x, self.split));
+            }
+        } else {
+            for (i, d) in self.changeset.diffs.iter().enumerate() {
+                let x = changed_text(self.changeset, i, self.whitespace);
+                match *d {
+                    Difference::Same(_) => write!(f, "{}{}", x, split)?,
+                    Difference::Add(_) => write!(f, "{{+{}+}}{}", x, split)?,
+                    Difference::Rem(_) => write!(f, "[-{}-]{}", x, split)?,
                 }
             }
         }
@@ -22,9 +224,35 @@ impl fmt::Display for Changeset {
     }
 }
 
+/// Whether colored output should be written to stdout.
+///
+/// Returns `false` if the `NO_COLOR` environment variable is set to a non-empty
+/// value (see <https://no-color.org>) or if stdout is not a terminal.
+/// `assert_diff!` uses this to choose between the colored `Display` output
+/// and `Changeset::plain`.
+pub fn use_color() -> bool {
+    color_enabled(env::var_os("NO_COLOR"), io::stdout().is_terminal())
+}
+
+fn color_enabled(no_color: Option<OsString>, is_terminal: bool) -> bool {
+    match no_color {
+        Some(ref x) if !x.is_empty() => false,
+        _ => is_terminal,
+    }
+}
+
+impl fmt::Display for Changeset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.styled(&Theme::default()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::Changeset;
+    use super::color_enabled;
+    use std::ffi::OsString;
+    use style::{Color, Style, Theme};
     use std::io::Write;
     use std::iter::FromIterator;
     use std::thread;
@@ -47,7 +275,7 @@ mod tests {
         }
         println!("Repr Result:");
         repr_bytes(result);
-        println!("");
+        println!();
         println!("--Result Repr DONE");
 
         println!("Debug Expected:");
@@ -56,7 +284,7 @@ mod tests {
         }
         println!("Repr Expected:");
         repr_bytes(expected);
-        println!("");
+        println!();
         println!("--Expected Repr DONE");
     }
 
@@ -68,7 +296,7 @@ mod tests {
                 // 9 => print!("{}", *b as char), // TAB
                 b'\n' => print!("\\n"),
                 b'\r' => print!("\\r"),
-                32...126 => print!("{}", *b as char), // visible ASCII
+                32..=126 => print!("{}", *b as char), // visible ASCII
                 _ => print!(r"\x{:0>2x}", b),
 
             }
@@ -97,4 +325,70 @@ mod tests {
         assert_eq!(result, vb(expected));
 
     }
+
+    #[test]
+    fn test_display_styled() {
+        let theme = Theme {
+            same: Style::new().fg(Color::BrightBlack),
+            add: Style::new().fg(Color::Blue).underline(),
+            rem: Style::new().bg(Color::Rgb(255, 128, 0)),
+            ..Theme::default()
+        };
+        let expected = b"\x1b[90mRoses are\x1b[0m \x1b[48;2;255;128;0mred,\x1b[0m \
+            \x1b[4;34mgreen,\x1b[0m \x1b[90mviolets\x1b[0m ";
+
+        let ch = Changeset::new("Roses are red, violets", "Roses are green, violets", " ");
+        let mut result: Vec<u8> = Vec::new();
+        write!(result, "{}", ch.styled(&theme)).unwrap();
+        debug_bytes(&result, expected);
+        assert_eq!(result, vb(expected));
+    }
+
+    #[test]
+    fn test_display_plain() {
+        let text1 = "Roses are red, violets are blue,\n\
+                     I wrote this library,\n\
+                     just for you.";
+
+        let text2 = "Roses are red, violets are blue,\n\
+                     I wrote this documentation,\n\
+                     just for you.\n\
+                     (It's true).";
+
+        let ch = Changeset::new(text1, text2, "\n");
+        assert_eq!(
+            ch.plain().to_string(),
+            " Roses are red, violets are blue,\n\
+             -I wrote this library,\n\
+             +I wrote this documentation,\n\
+             \x20just for you.\n\
+             +(It's true).\n"
+        );
+
+        let ch = Changeset::new("Rust is great", "Dust is grand", "");
+        assert_eq!(ch.plain().to_string(), "[-R-]{+D+}ust is gr[-e-]a[-t-]{+nd+}");
+    }
+
+    #[test]
+    fn test_color_enabled() {
+        assert!(color_enabled(None, true));
+        assert!(!color_enabled(None, false));
+        assert!(color_enabled(Some(OsString::new()), true));
+        assert!(!color_enabled(Some(OsString::from("1")), true));
+    }
+
+    #[test]
+    fn test_display_whitespace() {
+        let ch = Changeset::new("a\tb c\r\nd\n", "a b  c\r\nd", "\n");
+        assert_eq!(
+            ch.plain().show_whitespace().to_string(),
+            "-a→b·c␍\n+a·b··c␍\n d\n-⏎\n"
+        );
+        assert_eq!(ch.plain().to_string(), "-a\tb c\r\n+a b  c\r\n d\n-\n");
+
+        let ch = Changeset::new("x\ty", "x y", "");
+        let mut result: Vec<u8> = Vec::new();
+        write!(result, "{}", ch.styled(&Theme::default()).show_whitespace()).unwrap();
+        assert_eq!(result, vb(b"x\x1b[91m\xe2\x86\x92\x1b[0m\x1b[92m\xc2\xb7\x1b[0my"));
+    }
 }
@@ -0,0 +1,115 @@
+use lcs::split_tokens;
+
+/// Calculates the edit distance of two strings, i.e. the number of tokens that
+/// have to be removed and added to turn `orig` into `edit`, without building
+/// a changeset.
+///
+/// This is the same as the `edit_distance()` of `Changeset::new(orig, edit, split)`,
+/// but only needs memory proportional to the shorter text.
+///
+/// # Examples
+///
+/// ```
+/// use difference::{distance, Changeset};
+///
+/// assert_eq!(distance("The quick brown fox", "The slow brown dog", " "), 4);
+/// assert_eq!(distance("Rust", "Dust", ""), Changeset::new("Rust", "Dust", "").edit_distance());
+/// ```
+pub fn distance(orig: &str, edit: &str, split: &str) -> usize {
+    let a = split_tokens(orig, split);
+    let b = split_tokens(edit, split);
+    indel(&a, &b, None).unwrap()
+}
+
+/// Calculates the edit distance of two strings like `distance`, but stops as
+/// soon as it exceeds `k`.
+///
+/// Returns `None` if the distance is greater than `k`. Only changes that keep
+/// within `k` tokens of the diagonal are considered, so this takes time
+/// proportional to `k` times the length of the texts, which is much faster
+/// than `distance` for similar texts.
+///
+/// # Examples
+///
+/// ```
+/// use difference::distance_within;
+///
+/// assert_eq!(distance_within("kitten", "sitting", "", 5), Some(5));
+/// assert_eq!(distance_within("kitten", "sitting", "", 4), None);
+/// ```
+pub fn distance_within(orig: &str, edit: &str, split: &str, k: usize) -> Option<usize> {
+    let a = split_tokens(orig, split);
+    let b = split_tokens(edit, split);
+    indel(&a, &b, Some(k))
+}
+
+// the insert/delete distance of `a` and `b`, or `None` if it exceeds `bound`
+//
+// This computes the edit distance table row by row, keeping only the last
+// row, which has one cell per token of the shorter sequence. With a bound,
+// only the cells within `bound` of the diagonal are computed.
+fn indel<T: PartialEq>(a: &[T], b: &[T], bound: Option<usize>) -> Option<usize> {
+    let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+    let k = bound.unwrap_or(a.len() + b.len());
+    if a.len() - b.len() > k {
+        return None;
+    }
+
+    let m = b.len();
+    let mut prev: Vec<usize> = (0..m + 1).collect();
+    let mut cur: Vec<usize> = vec![usize::MAX; m + 1];
+    for i in 1..a.len() + 1 {
+        let lo = if i > k { i - k } else { 1 };
+        let hi = if i + k < m { i + k } else { m };
+
+        cur[lo - 1] = if lo == 1 && i <= k { i } else { usize::MAX };
+        let mut row_min = cur[lo - 1];
+        for j in lo..hi + 1 {
+            cur[j] = if a[i - 1] == b[j - 1] {
+                prev[j - 1]
+            } else {
+                prev[j].min(cur[j - 1]).saturating_add(1)
+            };
+            row_min = row_min.min(cur[j]);
+        }
+        if hi < m {
+            cur[hi + 1] = usize::MAX;
+        }
+        if row_min > k {
+            return None;
+        }
+        ::std::mem::swap(&mut prev, &mut cur);
+    }
+
+    if prev[m] > k {
+        None
+    } else {
+        Some(prev[m])
+    }
+}
+
+#[test]
+fn test_distance() {
+    use super::Changeset;
+
+    let cases = [
+        ("test", "tost", ""),
+        ("", "a b c", ""),
+        ("a b c", "", " "),
+        ("a b : c", "b a : b : c", " "),
+        ("The quick brown fox jumps over the lazy dog", "The quick brown dog leaps over the lazy cat", ""),
+        ("a\nb\nc", "a\nc\nd\n", "\n"),
+    ];
+    for &(orig, edit, split) in &cases {
+        let expected = Changeset::new(orig, edit, split).edit_distance();
+        assert_eq!(distance(orig, edit, split), expected);
+        assert_eq!(distance_within(orig, edit, split, expected), Some(expected));
+        assert_eq!(distance_within(orig, edit, split, expected + 3), Some(expected));
+        if expected > 0 {
+            assert_eq!(distance_within(orig, edit, split, expected - 1), None);
+        }
+    }
+
+    assert_eq!(distance_within("abc", "abcdef", "", 2), None);
+    assert_eq!(distance_within("", "", "", 0), Some(0));
+}
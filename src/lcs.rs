@@ -1,38 +1,56 @@
+use options::Normalizer;
+use std::borrow::Cow;
 use std::cmp::max;
 
 // strsplit is like `s.split(split)`, except that if `split` is "", it
 // trims the leading and trailing empty elements, since the `lcs`
 // logic won't handle those properly.
-fn strsplit<'a>(s: &'a str, split: &str) -> Vec<&'a str> {
+pub fn strsplit<'a>(s: &'a str, split: &str) -> Vec<&'a str> {
     let mut si = s.split(split);
-    if split == "" {
+    if split.is_empty() {
         si.next();
     }
     let mut v: Vec<&str> = si.collect();
-    if split == "" {
+    if split.is_empty() {
         v.pop();
     }
     v
 }
 
-// finds the longest common subsequences
-// outputs the edit distance and a string containing
-// all chars both inputs have in common
+// splits an input text into tokens like `strsplit`, except that an empty
+// text has no tokens rather than a single empty one
+pub fn split_tokens<'a>(s: &'a str, split: &str) -> Vec<&'a str> {
+    if s.is_empty() {
+        Vec::new()
+    } else {
+        strsplit(s, split)
+    }
+}
+
+// a step of the alignment of two token lists `a` and `b`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Op {
+    // a[i] and b[j] are the same
+    Same(usize, usize),
+    // a[i] was removed
+    Rem(usize),
+    // b[j] was added
+    Add(usize),
+}
+
+// finds the longest common subsequence of `a` and `b`
+// outputs the alignment of both inputs as a list of operations
+// that turn `a` into `b`
 //
 // This algorithm is based on
 // https://en.wikipedia.org/wiki/Longest_common_subsequence_problem#Code_for_the_dynamic_programming_solution
 #[allow(non_snake_case)]
-#[cfg_attr(feature = "cargo-clippy", allow(many_single_char_names))]
-pub fn lcs(orig: &str, edit: &str, split: &str) -> (i32, String) {
-    // make list by custom splits
-    let a = strsplit(orig, split);
-    let b = strsplit(edit, split);
-
+#[allow(clippy::many_single_char_names)]
+pub fn lcs<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Op> {
     let N = a.len();
     let M = b.len();
 
-    let mut idx: Vec<usize> = Vec::with_capacity(N * M);
-    idx.resize(N * M, 0);
+    let mut idx: Vec<usize> = vec![0; N * M];
 
     for i in 0..N {
         for j in 0..M {
@@ -56,38 +74,92 @@ pub fn lcs(orig: &str, edit: &str, split: &str) -> (i32, String) {
         }
     }
 
-    let mut i = (N as isize) - 1;
-    let mut j = (M as isize) - 1;
-    let mut lcs = Vec::new();
+    backtrack(a, b, |i, j| idx[i * M + j])
+}
+
+// outputs the alignment of `a` and `b` from their table of longest common
+// subsequences, where `idx(i, j)` is the length of the longest common
+// subsequence of `a[..=i]` and `b[..=j]`
+pub fn backtrack<T: PartialEq, F: Fn(usize, usize) -> usize>(a: &[T], b: &[T], idx: F) -> Vec<Op> {
+    let mut i = (a.len() as isize) - 1;
+    let mut j = (b.len() as isize) - 1;
+    let mut common = Vec::new();
     while i >= 0 && j >= 0 {
         let ui = i as usize;
         let uj = j as usize;
         if a[ui] == b[uj] {
-            lcs.push(a[ui]);
+            common.push(ui);
             i -= 1;
             j -= 1;
         } else if j == 0 && i == 0 {
             break;
-        } else if i == 0 || idx[ui * M + uj - 1] > idx[(ui - 1) * M + uj] {
+        } else if i == 0 || (j > 0 && idx(ui, uj - 1) > idx(ui - 1, uj)) {
             j -= 1;
         } else {
             i -= 1;
         }
     }
 
-    lcs.reverse();
-    ((N + M - 2 * lcs.len()) as i32, lcs.join(split))
+    common.reverse();
+    align(a, b, &common)
+}
+
+// aligns `a` and `b` along their common subsequence, given as indices into
+// `a`, matching each common token at its earliest position in both lists
+fn align<T: PartialEq>(a: &[T], b: &[T], common: &[usize]) -> Vec<Op> {
+    let mut ops = Vec::new();
+    let mut i = 0;
+    let mut j = 0;
+    for &k in common {
+        let c = &a[k];
+        while a[i] != *c {
+            ops.push(Op::Rem(i));
+            i += 1;
+        }
+        while b[j] != *c {
+            ops.push(Op::Add(j));
+            j += 1;
+        }
+        ops.push(Op::Same(i, j));
+        i += 1;
+        j += 1;
+    }
+    ops.extend((i..a.len()).map(Op::Rem));
+    ops.extend((j..b.len()).map(Op::Add));
+    ops
+}
+
+// finds the longest common subsequence of `a` and `b` like `lcs`, but
+// compares the tokens by their normalized form
+pub fn lcs_by<N: Normalizer + ?Sized>(a: &[&str], b: &[&str], normalizer: &N) -> Vec<Op> {
+    let ka: Vec<Cow<str>> = a.iter().map(|x| normalizer.normalize(x)).collect();
+    let kb: Vec<Cow<str>> = b.iter().map(|x| normalizer.normalize(x)).collect();
+    lcs(&ka, &kb)
+}
+
+#[cfg(test)]
+fn lcs_str(orig: &str, edit: &str, split: &str) -> (i32, String) {
+    let a = strsplit(orig, split);
+    let b = strsplit(edit, split);
+    let ops = lcs(&a, &b);
+    let common: Vec<&str> = ops.iter()
+        .filter_map(|op| match *op {
+            Op::Same(i, _) => Some(a[i]),
+            _ => None,
+        })
+        .collect();
+    ((a.len() + b.len() - 2 * common.len()) as i32, common.join(split))
 }
 
 #[test]
 fn test_lcs() {
-    assert_eq!(lcs("test", "tost", ""), (2, "tst".to_string()));
-    assert_eq!(lcs("test", "test", ""), (0, "test".to_string()));
+    assert_eq!(lcs_str("test", "tost", ""), (2, "tst".to_string()));
+    assert_eq!(lcs_str("test", "test", ""), (0, "test".to_string()));
 
-    assert_eq!(lcs("test", "test", " "), (0, "test".to_string()));
+    assert_eq!(lcs_str("test", "test", " "), (0, "test".to_string()));
 
     assert_eq!(
-        lcs(
+        lcs_str(
             "The quick brown fox jumps over the lazy dog",
             "The quick brown dog leaps over the lazy cat",
             "",
@@ -95,7 +167,7 @@ fn test_lcs() {
         (16, "The quick brown o ps over the lazy ".to_string())
     );
     assert_eq!(
-        lcs(
+        lcs_str(
             "The quick brown fox jumps over the lazy dog",
             "The quick brown dog leaps over the lazy cat",
             " ",
@@ -104,7 +176,7 @@ fn test_lcs() {
     );
 
     assert_eq!(
-        lcs(
+        lcs_str(
             "The quick brown fox jumps over the lazy dog",
             "The quick brown dog leaps over the lazy cat",
             "\n",
@@ -112,7 +184,7 @@ fn test_lcs() {
         (2, "".to_string())
     );
     assert_eq!(
-        lcs(
+        lcs_str(
             "The quick brown fox jumps over the lazy dog",
             "The quick brown fox jumps over the lazy dog",
             "\n",
@@ -121,11 +193,25 @@ fn test_lcs() {
     );
 
     assert_eq!(
-        lcs("a b : c", "b a : b : c", " "),
+        lcs_str("a b : c", "b a : b : c", " "),
         (2, "a b : c".to_string())
     );
 
-    assert_eq!(lcs("", "a b c", ""), (5, "".to_string()));
+    assert_eq!(lcs_str("", "a b c", ""), (5, "".to_string()));
 
-    assert_eq!(lcs("", " a", " "), (1, "".to_string()));
+    assert_eq!(lcs_str("", " a", " "), (1, "".to_string()));
+}
+
+#[test]
+fn test_lcs_ops() {
+    assert_eq!(
+        lcs(&["a", "b", "c"], &["a", "c", "d"]),
+        vec![Op::Same(0, 0), Op::Rem(1), Op::Same(2, 1), Op::Add(2)]
+    );
+    assert_eq!(lcs(&["a"], &["b"]), vec![Op::Rem(0), Op::Add(0)]);
+    assert_eq!(lcs::<&str>(&[], &["b"]), vec![Op::Add(0)]);
+    assert_eq!(
+        lcs(&["a", "b"], &["a", "b", "a", "b"]),
+        vec![Op::Same(0, 0), Op::Same(1, 1), Op::Add(2), Op::Add(3)]
+    );
 }
@@ -0,0 +1,503 @@
+use lcs::split_tokens;
+
+/// A step of an `Alignment`
+#[derive(Clone, PartialEq, Debug)]
+pub enum Edit {
+    /// Tokens that are the same in both strings
+    Same(String),
+    /// Tokens that were added
+    Add(String),
+    /// Tokens that were removed
+    Rem(String),
+    /// Tokens of the original string, followed by the tokens of the edited
+    /// string that replaced them one by one
+    Substitute(String, String),
+    /// Pairs of adjacent tokens of the original string, followed by the same
+    /// pairs swapped in the edited string
+    Transpose(String, String),
+}
+
+/// The alignment of two strings with substitutions, created by
+/// `Alignment::levenshtein`, `Alignment::damerau_levenshtein`,
+/// `Alignment::weighted` and `Alignment::weighted_damerau`
+///
+/// Unlike a `Changeset`, which only removes and adds tokens, an alignment
+/// counts replacing a token by another one as a single change.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Alignment {
+    /// The steps that turn the original string into the edited string
+    pub edits: Vec<Edit>,
+    /// The split used to tokenize both strings
+    pub split: String,
+    /// The number of tokens that were added, removed or substituted, plus the
+    /// number of transposed pairs
+    pub distance: usize,
+    /// The total cost of the changes, see `Alignment::weighted`. Without
+    /// weights, this is equal to `distance`.
+    pub cost: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Step {
+    Same,
+    Add,
+    Rem,
+    Substitute,
+    Transpose,
+}
+
+impl Alignment {
+    /// Aligns two strings with the smallest number of added, removed and
+    /// substituted tokens, i.e. their Levenshtein distance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Alignment, Edit};
+    ///
+    /// let alignment = Alignment::levenshtein("Rust", "Dust", "");
+    /// assert_eq!(alignment.distance, 1);
+    /// assert_eq!(alignment.edits, vec![
+    ///     Edit::Substitute("R".to_string(), "D".to_string()),
+    ///     Edit::Same("ust".to_string()),
+    /// ]);
+    /// ```
+    pub fn levenshtein(orig: &str, edit: &str, split: &str) -> Alignment {
+        Alignment::new(orig, edit, split, &Unit, false)
+    }
+
+    /// Aligns two strings like `Alignment::levenshtein`, but also counts
+    /// swapping two adjacent tokens as a single change. This is the optimal
+    /// string alignment variant of the Damerau-Levenshtein distance, where no
+    /// token is edited more than once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Alignment, Edit};
+    ///
+    /// let alignment = Alignment::damerau_levenshtein("recieve", "receive", "");
+    /// assert_eq!(alignment.distance, 1);
+    /// assert_eq!(alignment.edits, vec![
+    ///     Edit::Same("rec".to_string()),
+    ///     Edit::Transpose("ie".to_string(), "ei".to_string()),
+    ///     Edit::Same("ve".to_string()),
+    /// ]);
+    /// ```
+    pub fn damerau_levenshtein(orig: &str, edit: &str, split: &str) -> Alignment {
+        Alignment::new(orig, edit, split, &Unit, true)
+    }
+
+    /// Aligns two strings with the smallest total cost of added, removed and
+    /// substituted tokens, where the cost of each change is given by `costs`.
+    /// The total cost is reported as `cost`, while `distance` still counts the
+    /// changed tokens.
+    ///
+    /// # Examples
+    ///
+    /// Correcting OCR output, where spaces are often missed and digits are
+    /// rarely misread:
+    ///
+    /// ```
+    /// use difference::{Alignment, Costs, Edit};
+    ///
+    /// struct Ocr;
+    ///
+    /// impl Costs for Ocr {
+    ///     fn add(&self, token: &str) -> f64 {
+    ///         if token == " " { 0.1 } else { 1.0 }
+    ///     }
+    ///
+    ///     fn rem(&self, token: &str) -> f64 {
+    ///         if token.chars().all(|c| c.is_ascii_digit()) { 10.0 } else { 1.0 }
+    ///     }
+    /// }
+    ///
+    /// let alignment = Alignment::weighted("total:42", "total: 42", "", &Ocr);
+    /// assert_eq!(alignment.cost, 0.1);
+    /// assert_eq!(alignment.edits, vec![
+    ///     Edit::Same("total:".to_string()),
+    ///     Edit::Add(" ".to_string()),
+    ///     Edit::Same("42".to_string()),
+    /// ]);
+    /// ```
+    pub fn weighted<C: Costs + ?Sized>(orig: &str, edit: &str, split: &str, costs: &C) -> Alignment {
+        Alignment::new(orig, edit, split, costs, false)
+    }
+
+    /// Aligns two strings like `Alignment::weighted`, but also allows swapping
+    /// two adjacent tokens, at the cost given by `Costs::transpose`, like
+    /// `Alignment::damerau_levenshtein`.
+    ///
+    /// # Examples
+    ///
+    /// Typos that swap letters are cheaper than other mistakes:
+    ///
+    /// ```
+    /// use difference::{Alignment, Costs, Edit};
+    ///
+    /// struct Typos;
+    ///
+    /// impl Costs for Typos {
+    ///     fn transpose(&self, _: &str, _: &str) -> f64 {
+    ///         0.5
+    ///     }
+    /// }
+    ///
+    /// let alignment = Alignment::weighted_damerau("teh cat", "the cat", "", &Typos);
+    /// assert_eq!(alignment.cost, 0.5);
+    /// assert_eq!(alignment.edits, vec![
+    ///     Edit::Same("t".to_string()),
+    ///     Edit::Transpose("eh".to_string(), "he".to_string()),
+    ///     Edit::Same(" cat".to_string()),
+    /// ]);
+    /// ```
+    pub fn weighted_damerau<C: Costs + ?Sized>(orig: &str, edit: &str, split: &str, costs: &C) -> Alignment {
+        Alignment::new(orig, edit, split, costs, true)
+    }
+
+    #[allow(non_snake_case)]
+    fn new<C: Costs + ?Sized>(orig: &str, edit: &str, split: &str, costs: &C, transpositions: bool) -> Alignment {
+        let a = split_tokens(orig, split);
+        let b = split_tokens(edit, split);
+        let N = a.len();
+        let M = b.len();
+
+        // a NaN cost makes a change impossible, like an infinite one
+        let valid = |cost: f64| if cost.is_nan() { f64::INFINITY } else { cost };
+        // the cost of the cheapest way to reach cell (i, j) of the table by `step`
+        let step_cost = |d: &[f64], step: Step, i: usize, j: usize| -> Option<f64> {
+            let W = M + 1;
+            match step {
+                Step::Same if i > 0 && j > 0 && a[i - 1] == b[j - 1] => Some(d[(i - 1) * W + j - 1]),
+                Step::Substitute if i > 0 && j > 0 && a[i - 1] != b[j - 1] => {
+                    Some(d[(i - 1) * W + j - 1] + valid(costs.substitute(a[i - 1], b[j - 1])))
+                }
+                Step::Transpose if transpositions && transposed(&a, &b, i, j) => {
+                    Some(d[(i - 2) * W + j - 2] + valid(costs.transpose(a[i - 2], a[i - 1])))
+                }
+                Step::Add if j > 0 => Some(d[i * W + j - 1] + valid(costs.add(b[j - 1]))),
+                Step::Rem if i > 0 => Some(d[(i - 1) * W + j] + valid(costs.rem(a[i - 1]))),
+                _ => None,
+            }
+        };
+        // the order in which steps are preferred on ties
+        let steps = [Step::Same, Step::Transpose, Step::Substitute, Step::Add, Step::Rem];
+
+        // d[i * (M + 1) + j] is the cost of aligning a[..i] and b[..j]
+        let W = M + 1;
+        let mut d: Vec<f64> = vec![0.0; (N + 1) * W];
+        for i in 0..N + 1 {
+            for j in 0..M + 1 {
+                if i > 0 || j > 0 {
+                    d[i * W + j] = steps
+                        .iter()
+                        .filter_map(|&step| step_cost(&d, step, i, j))
+                        .fold(f64::INFINITY, f64::min);
+                }
+            }
+        }
+
+        // the cell costs were computed by the same expressions, so the step
+        // that reached a cell compares equal, unless negative costs made it
+        // NaN; then any possible step is taken
+        let mut path = Vec::new();
+        let (mut i, mut j) = (N, M);
+        while i > 0 || j > 0 {
+            let x = d[i * W + j];
+            let step = steps
+                .iter()
+                .find(|&&step| step_cost(&d, step, i, j) == Some(x))
+                .or_else(|| steps.iter().find(|&&step| step_cost(&d, step, i, j).is_some()))
+                .cloned()
+                .unwrap_or(Step::Add);
+            path.push((step, i, j));
+            match step {
+                Step::Same | Step::Substitute => {
+                    i -= 1;
+                    j -= 1;
+                }
+                Step::Transpose => {
+                    i -= 2;
+                    j -= 2;
+                }
+                Step::Add => j -= 1,
+                Step::Rem => i -= 1,
+            }
+        }
+        path.reverse();
+
+        // joins the tokens of consecutive steps of the same kind
+        let mut edits = Vec::new();
+        let mut distance = 0;
+        let mut k = 0;
+        while k < path.len() {
+            let step = path[k].0;
+            let (mut x, mut y): (Vec<&str>, Vec<&str>) = (Vec::new(), Vec::new());
+            while k < path.len() && path[k].0 == step {
+                let (_, i, j) = path[k];
+                match step {
+                    Step::Same | Step::Substitute => {
+                        x.push(a[i - 1]);
+                        y.push(b[j - 1]);
+                    }
+                    Step::Transpose => {
+                        x.extend_from_slice(&a[i - 2..i]);
+                        y.extend_from_slice(&b[j - 2..j]);
+                    }
+                    Step::Add => y.push(b[j - 1]),
+                    Step::Rem => x.push(a[i - 1]),
+                }
+                if step != Step::Same {
+                    distance += 1;
+                }
+                k += 1;
+            }
+            let (x, y) = (x.join(split), y.join(split));
+            edits.push(match step {
+                Step::Same => Edit::Same(x),
+                Step::Add => Edit::Add(y),
+                Step::Rem => Edit::Rem(x),
+                Step::Substitute => Edit::Substitute(x, y),
+                Step::Transpose => Edit::Transpose(x, y),
+            });
+        }
+
+        Alignment {
+            edits,
+            split: split.to_string(),
+            distance,
+            cost: d[N * W + M],
+        }
+    }
+}
+
+/// The costs of changes, used to find the cheapest alignment of two strings
+/// with `Alignment::weighted`
+///
+/// Every change costs `1.0` by default, which gives the Levenshtein distance.
+/// Costs should not be negative. A cost of `f64::NAN` or `f64::INFINITY`
+/// rules out the change, unless there is no other way to align the strings.
+pub trait Costs {
+    /// The cost of adding `token`
+    fn add(&self, token: &str) -> f64 {
+        let _ = token;
+        1.0
+    }
+
+    /// The cost of removing `token`
+    fn rem(&self, token: &str) -> f64 {
+        let _ = token;
+        1.0
+    }
+
+    /// The cost of replacing `orig` by the different token `edit`
+    fn substitute(&self, orig: &str, edit: &str) -> f64 {
+        let _ = (orig, edit);
+        1.0
+    }
+
+    /// The cost of swapping the adjacent tokens `first` and `second`.
+    /// Only used by `Alignment::weighted_damerau`.
+    fn transpose(&self, first: &str, second: &str) -> f64 {
+        let _ = (first, second);
+        1.0
+    }
+}
+
+// every change costs 1
+struct Unit;
+
+impl Costs for Unit {}
+
+// whether a[i - 2..i] are b[j - 2..j] swapped
+fn transposed(a: &[&str], b: &[&str], i: usize, j: usize) -> bool {
+    i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] && a[i - 1] != a[i - 2]
+}
+
+/// Calculates the Levenshtein distance of two strings, i.e. the number of
+/// tokens that have to be added, removed or substituted to turn `orig` into
+/// `edit`. Unlike `distance`, a substitution counts as a single change.
+///
+/// This only needs memory proportional to the shorter text, see
+/// `Alignment::levenshtein` for the changes themselves.
+///
+/// # Examples
+///
+/// ```
+/// use difference::{distance, levenshtein};
+///
+/// assert_eq!(levenshtein("Rust", "Dust", ""), 1);
+/// assert_eq!(distance("Rust", "Dust", ""), 2);
+/// assert_eq!(levenshtein("kitten", "sitting", ""), 3);
+/// ```
+pub fn levenshtein(orig: &str, edit: &str, split: &str) -> usize {
+    rows(&split_tokens(orig, split), &split_tokens(edit, split), false)
+}
+
+/// Calculates the optimal string alignment distance of two strings, like
+/// `levenshtein`, but also counting swapping two adjacent tokens as a single
+/// change, as long as no token is edited more than once.
+///
+/// # Examples
+///
+/// ```
+/// use difference::{damerau_levenshtein, levenshtein};
+///
+/// assert_eq!(damerau_levenshtein("recieve", "receive", ""), 1);
+/// assert_eq!(levenshtein("recieve", "receive", ""), 2);
+/// ```
+pub fn damerau_levenshtein(orig: &str, edit: &str, split: &str) -> usize {
+    rows(&split_tokens(orig, split), &split_tokens(edit, split), true)
+}
+
+// the Levenshtein or optimal string alignment distance of `a` and `b`,
+// keeping only the last rows of the table, which are as long as the shorter
+// sequence
+fn rows(a: &[&str], b: &[&str], transpositions: bool) -> usize {
+    let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+    let m = b.len();
+
+    let mut before: Vec<usize> = vec![0; m + 1];
+    let mut prev: Vec<usize> = (0..m + 1).collect();
+    let mut cur: Vec<usize> = vec![0; m + 1];
+    for i in 1..a.len() + 1 {
+        cur[0] = i;
+        for j in 1..m + 1 {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if transpositions && transposed(a, b, i, j) {
+                cur[j] = cur[j].min(before[j - 2] + 1);
+            }
+        }
+        ::std::mem::swap(&mut before, &mut prev);
+        ::std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[m]
+}
+
+#[test]
+fn test_levenshtein() {
+    assert_eq!(levenshtein("", "", ""), 0);
+    assert_eq!(levenshtein("", "abc", ""), 3);
+    assert_eq!(levenshtein("flaw", "lawn", ""), 2);
+    assert_eq!(levenshtein("a b c", "a x c d", " "), 2);
+
+    assert_eq!(damerau_levenshtein("ca", "abc", ""), 3);
+    assert_eq!(damerau_levenshtein("abcd", "badc", ""), 2);
+    assert_eq!(damerau_levenshtein("a b c", "b a c", " "), 1);
+
+    let cases = [("kitten", "sitting"), ("ca", "abc"), ("abcdef", "badcfe"), ("aab", "aba"), ("", "x")];
+    for &(x, y) in &cases {
+        assert_eq!(Alignment::levenshtein(x, y, "").distance, levenshtein(x, y, ""));
+        assert_eq!(levenshtein(y, x, ""), levenshtein(x, y, ""));
+        assert_eq!(
+            Alignment::damerau_levenshtein(x, y, "").distance,
+            damerau_levenshtein(x, y, "")
+        );
+    }
+}
+
+#[test]
+fn test_alignment() {
+    let alignment = Alignment::levenshtein("kitten", "sitting", "");
+    assert_eq!(
+        alignment.edits,
+        vec![
+            Edit::Substitute("k".to_string(), "s".to_string()),
+            Edit::Same("itt".to_string()),
+            Edit::Substitute("e".to_string(), "i".to_string()),
+            Edit::Same("n".to_string()),
+            Edit::Add("g".to_string()),
+        ]
+    );
+
+    let alignment = Alignment::damerau_levenshtein("a b c d", "b a c", " ");
+    assert_eq!(alignment.distance, 2);
+    assert_eq!(
+        alignment.edits,
+        vec![
+            Edit::Transpose("a b".to_string(), "b a".to_string()),
+            Edit::Same("c".to_string()),
+            Edit::Rem("d".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_alignment_empty() {
+    // an empty text has no tokens, not a single empty one
+    let alignment = Alignment::levenshtein("", "a", " ");
+    assert_eq!(alignment.distance, 1);
+    assert_eq!(alignment.edits, vec![Edit::Add("a".to_string())]);
+
+    let alignment = Alignment::levenshtein("a b", "", " ");
+    assert_eq!(alignment.distance, 2);
+    assert_eq!(alignment.edits, vec![Edit::Rem("a b".to_string())]);
+
+    assert_eq!(Alignment::levenshtein("", "", "\n").edits, vec![]);
+    assert_eq!(levenshtein("", "a b", " "), 2);
+}
+
+#[test]
+fn test_weighted() {
+    // substitutions are more expensive than a removal and an addition
+    struct NoSubstitutions;
+    impl Costs for NoSubstitutions {
+        fn substitute(&self, _: &str, _: &str) -> f64 {
+            3.0
+        }
+    }
+    let alignment = Alignment::weighted("a b c", "a x c", " ", &NoSubstitutions);
+    assert_eq!(alignment.cost, 2.0);
+    assert_eq!(alignment.distance, 2);
+    assert_eq!(
+        alignment.edits,
+        vec![
+            Edit::Same("a".to_string()),
+            Edit::Rem("b".to_string()),
+            Edit::Add("x".to_string()),
+            Edit::Same("c".to_string()),
+        ]
+    );
+
+    let alignment = Alignment::weighted("kitten", "sitting", "", &Unit);
+    assert_eq!(alignment, Alignment::levenshtein("kitten", "sitting", ""));
+    assert_eq!(alignment.cost, 3.0);
+}
+
+#[test]
+fn test_weighted_damerau() {
+    struct Swaps(f64);
+    impl Costs for Swaps {
+        fn transpose(&self, _: &str, _: &str) -> f64 {
+            self.0
+        }
+    }
+    let alignment = Alignment::weighted_damerau("a b c", "b a c", " ", &Swaps(0.5));
+    assert_eq!(alignment.cost, 0.5);
+    assert_eq!(alignment.edits[0], Edit::Transpose("a b".to_string(), "b a".to_string()));
+
+    // expensive transpositions are replaced by substitutions
+    let alignment = Alignment::weighted_damerau("a b c", "b a c", " ", &Swaps(3.0));
+    assert_eq!(alignment.cost, 2.0);
+
+    let alignment = Alignment::weighted_damerau("recieve", "receive", "", &Unit);
+    assert_eq!(alignment, Alignment::damerau_levenshtein("recieve", "receive", ""));
+}
+
+#[test]
+fn test_weighted_nan() {
+    // NaN costs rule out changes instead of panicking
+    struct NoAdd;
+    impl Costs for NoAdd {
+        fn add(&self, _: &str) -> f64 {
+            f64::NAN
+        }
+    }
+    let alignment = Alignment::weighted("ab", "abc", "", &NoAdd);
+    assert_eq!(alignment.cost, f64::INFINITY);
+
+    let alignment = Alignment::weighted("ab", "ac", "", &NoAdd);
+    assert_eq!(alignment.cost, 1.0);
+}
@@ -7,7 +7,7 @@
 //!
 //! ```toml
 //! [dependencies]
-//! difference = "2.0"
+//! difference = "3.0"
 //! ```
 //!
 //! Now you can use the crate in your code
@@ -38,17 +38,66 @@
 #![deny(missing_docs)]
 #![deny(warnings)]
 
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "termcolor")]
+extern crate termcolor;
+#[cfg(feature = "unicode-normalization")]
+extern crate unicode_normalization;
+#[cfg(feature = "unicode-segmentation")]
+extern crate unicode_segmentation;
+
 mod lcs;
+mod levenshtein;
 mod merge;
+mod cleanup;
+mod close_matches;
 mod display;
-
-use lcs::lcs;
+mod distance;
+mod line_ending;
+mod markdown;
+mod moved;
+mod numbered;
+mod opcodes;
+mod options;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod refine;
+mod slide;
+mod stats;
+mod stream;
+mod style;
+mod tokenize;
+mod word_diff;
+mod xdiff;
+
+use lcs::{lcs, split_tokens, strsplit};
 use merge::merge;
 
+pub use close_matches::close_matches;
+pub use display::{use_color, Plain, Styled};
+pub use distance::{distance, distance_within};
+pub use levenshtein::{damerau_levenshtein, levenshtein, Alignment, Costs, Edit};
+pub use line_ending::LineEnding;
+pub use markdown::Markdown;
+pub use moved::Move;
+pub use numbered::Numbered;
+pub use opcodes::{Opcode, Tag};
+pub use options::{Case, Normalization, Normalizer, Options, Whitespace};
+pub use refine::{Html, Refined, RefinedStyled, Refinement};
+pub use stats::Stats;
+pub use style::{Color, Painted, Style, Theme};
+pub use tokenize::{Lines, Tokenizer};
+#[cfg(feature = "unicode-segmentation")]
+pub use tokenize::{Graphemes, Words};
+pub use word_diff::{WordDiff, WordDiffMode};
+
 /// Defines the contents of a changeset
 /// Changesets will be delivered in order of appearance in the original string
 /// Sequences of the same kind will be grouped into one Difference
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum Difference {
     /// Sequences that are the same
     Same(String),
@@ -59,6 +108,7 @@ pub enum Difference {
 }
 
 /// The information about a full changeset
+#[derive(Clone, PartialEq, Debug)]
 pub struct Changeset {
     /// An ordered vector of `Difference` objects, coresponding
     /// to the differences within the text
@@ -66,8 +116,16 @@ pub struct Changeset {
     /// The split used when creating the `Changeset`
     /// Common splits are `""` for char-level, `" "` for word-level and `"\n"` for line-level.
     pub split: String,
-    /// The edit distance of the `Changeset`
+    /// The edit distance of the `Changeset`, as an `i32` for compatibility.
+    /// It saturates at `i32::MAX`, use `Changeset::edit_distance` for huge inputs.
+    ///
+    /// This is not updated when `diffs` is changed, unlike `Changeset::edit_distance`.
     pub distance: i32,
+    // the token boundaries of every difference, if the texts were split by a
+    // tokenizer rather than by `split`
+    bounds: Option<Vec<Bounds>>,
+    // whether the tokens are whole lines including their terminators, see `Lines`
+    lines: bool,
 }
 
 impl Changeset {
@@ -96,15 +154,192 @@ impl Changeset {
     /// ]);
     /// ```
     pub fn new(orig: &str, edit: &str, split: &str) -> Changeset {
-        let (dist, common) = lcs(orig, edit, split);
-        Changeset {
-            diffs: merge(orig, edit, &common, split),
+        let a = split_tokens(orig, split);
+        let b = split_tokens(edit, split);
+        Changeset::from_kinds(&merge(&a, &b, &lcs(&a, &b), |_, _, _, _| false), split, false)
+    }
+
+    /// Creates a changeset from differences whose tokens are joined by `split`,
+    /// e.g. ones taken from another changeset or built by hand.
+    ///
+    /// The edit distance is the number of removed and added tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference};
+    ///
+    /// let changeset = Changeset::from_diffs(vec![
+    ///     Difference::Same("a".to_string()),
+    ///     Difference::Rem("b c".to_string()),
+    ///     Difference::Add("d".to_string()),
+    /// ], " ");
+    ///
+    /// assert_eq!(changeset.distance, 3);
+    /// assert_eq!(changeset.to_string(), Changeset::new("a b c", "a d", " ").to_string());
+    /// ```
+    pub fn from_diffs(diffs: Vec<Difference>, split: &str) -> Changeset {
+        let mut changeset = Changeset {
+            diffs,
             split: split.to_string(),
-            distance: dist,
+            distance: 0,
+            bounds: None,
+            lines: false,
+        };
+        changeset.distance = saturate(changeset.edit_distance());
+        changeset
+    }
+
+    /// The edit distance of the `Changeset`, i.e. the number of removed and
+    /// added tokens. Unlike `distance`, this does not saturate at `i32::MAX`.
+    ///
+    /// It is counted on `diffs`, so it stays accurate when they are changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::Changeset;
+    ///
+    /// let changeset = Changeset::new("a b c", "a x c d", " ");
+    /// assert_eq!(changeset.edit_distance(), 3);
+    /// ```
+    pub fn edit_distance(&self) -> usize {
+        let mut dist = 0;
+        for (i, d) in self.diffs.iter().enumerate() {
+            if let Difference::Same(_) = *d {
+                continue;
+            }
+            dist += self.tokens(i).len();
+        }
+        dist
+    }
+
+    // creates a changeset from a list of tokens and their kinds, recording the
+    // token boundaries if the tokens come from a tokenizer
+    fn from_kinds(tokens: &[(Kind, &str)], split: &str, tokenized: bool) -> Changeset {
+        let (diffs, bounds) = join(tokens, split, tokenized);
+        let mut changeset = Changeset::from_diffs(diffs, split);
+        changeset.bounds = bounds;
+        changeset.distance = saturate(changeset.edit_distance());
+        changeset
+    }
+
+    // the tokens of the `i`th difference, at the recorded token boundaries of
+    // a tokenized changeset, or split by `split`
+    //
+    // The boundaries are only used while the difference has the text they
+    // were recorded for, since `diffs` may be changed.
+    fn tokens(&self, i: usize) -> Vec<&str> {
+        let x = match self.diffs[i] {
+            Difference::Same(ref x) | Difference::Rem(ref x) | Difference::Add(ref x) => x,
+        };
+        match self.bounds.as_ref().and_then(|b| b.get(i)) {
+            Some((text, lens)) if text == x => {
+                let mut tokens = Vec::with_capacity(lens.len());
+                let mut start = 0;
+                for &len in lens {
+                    tokens.push(&x[start..start + len]);
+                    start += len;
+                }
+                tokens
+            }
+            _ if self.lines => x.split_inclusive('\n').collect(),
+            _ => strsplit(x, &self.split),
+        }
+    }
+
+    // the tokens of all differences and their kinds
+    fn kinds(&self) -> Vec<(Kind, &str)> {
+        let mut ret = Vec::new();
+        for (i, d) in self.diffs.iter().enumerate() {
+            let kind = Kind::of(d);
+            ret.extend(self.tokens(i).into_iter().map(|x| (kind, x)));
+        }
+        ret
+    }
+
+    // whether the changeset is rendered line by line, because it is split by
+    // line breaks or into whole lines by `Lines`
+    fn line_based(&self) -> bool {
+        self.lines || self.split.contains('\n')
+    }
+
+    // splits the text of the `i`th difference of a line-based changeset into
+    // lines, without their terminators
+    fn split_lines(&self, i: usize) -> Vec<&str> {
+        if self.lines {
+            self.tokens(i).into_iter().map(|l| line_ending::split_terminator(l).0).collect()
+        } else {
+            match self.diffs[i] {
+                Difference::Same(ref x) | Difference::Rem(ref x) | Difference::Add(ref x) => {
+                    x.split(self.split.as_str()).collect()
+                }
+            }
         }
     }
 }
 
+// the text of a difference and the byte lengths of its tokens
+type Bounds = (String, Vec<usize>);
+
+// the kind of a difference, for passes that work token by token
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Kind {
+    Same,
+    Rem,
+    Add,
+}
+
+impl Kind {
+    fn of(d: &Difference) -> Kind {
+        match *d {
+            Difference::Same(_) => Kind::Same,
+            Difference::Rem(_) => Kind::Rem,
+            Difference::Add(_) => Kind::Add,
+        }
+    }
+
+    fn diff(self, x: String) -> Difference {
+        match self {
+            Kind::Same => Difference::Same(x),
+            Kind::Rem => Difference::Rem(x),
+            Kind::Add => Difference::Add(x),
+        }
+    }
+}
+
+// joins consecutive tokens of the same kind by `split` into differences,
+// and outputs their texts and the byte lengths of their tokens if `tokenized`
+fn join(tokens: &[(Kind, &str)], split: &str, tokenized: bool) -> (Vec<Difference>, Option<Vec<Bounds>>) {
+    let mut diffs = Vec::new();
+    let mut bounds = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let kind = tokens[i].0;
+        let mut j = i + 1;
+        while j < tokens.len() && tokens[j].0 == kind {
+            j += 1;
+        }
+        let run: Vec<&str> = tokens[i..j].iter().map(|t| t.1).collect();
+        let x = run.join(split);
+        if tokenized {
+            bounds.push((x.clone(), run.iter().map(|x| x.len()).collect()));
+        }
+        diffs.push(kind.diff(x));
+        i = j;
+    }
+    (diffs, if tokenized { Some(bounds) } else { None })
+}
+
+// converts a distance for the `i32` field, without wrapping around
+fn saturate(dist: usize) -> i32 {
+    if dist > i32::MAX as usize {
+        i32::MAX
+    } else {
+        dist as i32
+    }
+}
+
 /// **This function is deprecated, please use `Changeset::new` instead**
 ///
 /// Calculates the edit distance and the changeset for two given strings.
@@ -152,9 +387,11 @@ pub fn diff(orig: &str, edit: &str, split: &str) -> (i32, Vec<Difference>) {
 ///
 /// Remember that edit distance might not be equal to your understanding of difference,
 /// for example the words "Rust" and "Dust" have an edit distance of 2 because two changes (a
-/// removal and an addition) are required to make them look the same.
+/// removal and an addition) are required to make them look the same. Use `levenshtein`
+/// for a distance that counts a substitution as a single change.
 ///
-/// Will print an error with a colorful diff in case of failure.
+/// Will print an error with a colorful diff in case of failure. The diff is printed
+/// without colors if stdout is not a terminal or `NO_COLOR` is set, see `use_color`.
 #[macro_export]
 macro_rules! assert_diff {
     ($orig:expr , $edit:expr, $split: expr, $expected: expr) => ({
@@ -163,7 +400,11 @@ macro_rules! assert_diff {
 
         let changeset = $crate::Changeset::new(orig, edit, &($split));
         if changeset.distance != $expected {
-            println!("{}", changeset);
+            if $crate::use_color() {
+                println!("{}", changeset);
+            } else {
+                println!("{}", changeset.plain());
+            }
             panic!("assertion failed: edit distance between {:?} and {:?} is {} and not {}, see \
                     diffset above",
                    orig,
@@ -334,3 +575,59 @@ fn test_assert_diff() {
 
     assert_diff!(text1, text2, " ", 2);
 }
+
+#[test]
+fn test_earliest_match() {
+    // text appended after a repeated token or line follows the original text
+    assert_eq!(
+        Changeset::new("test", "testtest", "").diffs,
+        vec![
+            Difference::Same("test".to_string()),
+            Difference::Add("test".to_string()),
+        ]
+    );
+    assert_eq!(
+        Changeset::new("fn a() {\n}\n", "fn a() {\n}\nfn b() {\n}\n", "\n").diffs,
+        vec![
+            Difference::Same("fn a() {\n}".to_string()),
+            Difference::Add("fn b() {\n}".to_string()),
+            Difference::Same("".to_string()),
+        ]
+    );
+
+    // an empty string has no tokens
+    let changeset = Changeset::new("", "a", " ");
+    assert_eq!(changeset.diffs, vec![Difference::Add("a".to_string())]);
+    assert_eq!(changeset.plain().to_string(), "{+a+} ");
+    assert_eq!(
+        Changeset::new("", "a\nb", "\n").plain().to_string(),
+        "+a\n+b\n"
+    );
+}
+
+#[test]
+fn test_edit_distance() {
+    let changeset = Changeset::new("a b c", "a x c d", " ");
+    assert_eq!(changeset.edit_distance(), 3);
+    assert_eq!(changeset.distance, 3);
+
+    // the edit distance follows changes of the differences
+    let mut changeset = changeset;
+    changeset.diffs.pop();
+    assert_eq!(changeset.edit_distance(), 2);
+    assert_eq!(
+        Changeset::from_diffs(changeset.diffs.clone(), " "),
+        Changeset::new("a b c", "a x c", " ")
+    );
+
+    // every reported token is counted, and no other
+    for &(orig, edit, split, dist) in &[("", "bcac", " ", 1), ("a\n", "c\n", "\n", 2), ("", "", " ", 0)] {
+        let changeset = Changeset::new(orig, edit, split);
+        assert_eq!(changeset.edit_distance(), dist);
+        assert_eq!(changeset.distance, dist as i32);
+        assert_eq!(::distance(orig, edit, split), dist);
+    }
+
+    assert_eq!(saturate(7), 7);
+    assert_eq!(saturate(i32::MAX as usize + 1), i32::MAX);
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+
+/// The line terminators used by a text, see `LineEnding::detect`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineEnding {
+    /// Lines end with `"\n"`, like on Unix
+    Lf,
+    /// Lines end with `"\r\n"`, like on Windows
+    CrLf,
+    /// Lines end with `"\r"`, like on classic Mac OS
+    Cr,
+    /// Lines end with more than one kind of terminator
+    Mixed,
+}
+
+impl LineEnding {
+    /// Detects the line terminators used by `text`.
+    /// Returns `None` if `text` has no line terminators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::detect("a\r\nb\r\n"), Some(LineEnding::CrLf));
+    /// assert_eq!(LineEnding::detect("a\nb\r\n"), Some(LineEnding::Mixed));
+    /// assert_eq!(LineEnding::detect("a"), None);
+    /// ```
+    pub fn detect(text: &str) -> Option<LineEnding> {
+        let mut found = None;
+        let mut bytes = text.bytes().peekable();
+        while let Some(b) = bytes.next() {
+            let ending = match b {
+                b'\n' => LineEnding::Lf,
+                b'\r' if bytes.peek() == Some(&b'\n') => {
+                    bytes.next();
+                    LineEnding::CrLf
+                }
+                b'\r' => LineEnding::Cr,
+                _ => continue,
+            };
+            match found {
+                None => found = Some(ending),
+                Some(x) if x != ending => return Some(LineEnding::Mixed),
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Compares the line terminators of two texts, so that a conversion between
+    /// line endings can be reported separately from the diff of the texts, e.g.
+    /// when comparing them with `Options::ignore_line_endings`.
+    ///
+    /// Returns the line endings of `orig` and `edit` if both texts have line
+    /// terminators and they differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::LineEnding;
+    ///
+    /// if let Some((before, after)) = LineEnding::changed("a\r\nb\r\n", "a\nb\n") {
+    ///     assert_eq!(format!("line endings changed from {} to {}", before, after),
+    ///                "line endings changed from CRLF to LF");
+    /// }
+    /// assert_eq!(LineEnding::changed("a\nb", "a\nc\n"), None);
+    /// ```
+    pub fn changed(orig: &str, edit: &str) -> Option<(LineEnding, LineEnding)> {
+        match (LineEnding::detect(orig), LineEnding::detect(edit)) {
+            (Some(a), Some(b)) if a != b => Some((a, b)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Cr => "CR",
+            LineEnding::Mixed => "mixed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// splits `text` into lines, each including its `"\r\n"`, `"\n"` or `"\r"`
+// terminator
+pub fn lines(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut ret = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        i += match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => 2,
+            b'\r' | b'\n' => 1,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        ret.push(&text[start..i]);
+        start = i;
+    }
+    if start < text.len() {
+        ret.push(&text[start..]);
+    }
+    ret
+}
+
+// splits a line into its text and its terminator
+pub fn split_terminator(line: &str) -> (&str, &str) {
+    let len = if line.ends_with("\r\n") {
+        2
+    } else if line.ends_with('\n') || line.ends_with('\r') {
+        1
+    } else {
+        0
+    };
+    line.split_at(line.len() - len)
+}
+
+#[test]
+fn test_detect() {
+    assert_eq!(LineEnding::detect(""), None);
+    assert_eq!(LineEnding::detect("a\nb"), Some(LineEnding::Lf));
+    assert_eq!(LineEnding::detect("a\r\nb\r\n"), Some(LineEnding::CrLf));
+    assert_eq!(LineEnding::detect("a\rb\r"), Some(LineEnding::Cr));
+    assert_eq!(LineEnding::detect("a\r\nb\r"), Some(LineEnding::Mixed));
+    assert_eq!(LineEnding::detect("a\r\r\n"), Some(LineEnding::Mixed));
+
+    assert_eq!(
+        LineEnding::changed("a\rb", "a\r\nb"),
+        Some((LineEnding::Cr, LineEnding::CrLf))
+    );
+    assert_eq!(LineEnding::changed("a\nb", "ab"), None);
+}
+
+#[test]
+fn test_lines() {
+    assert_eq!(lines("a\r\nb\rc\n\nd"), vec!["a\r\n", "b\r", "c\n", "\n", "d"]);
+    assert_eq!(lines(""), Vec::<&str>::new());
+    assert_eq!(split_terminator("a\r\n"), ("a", "\r\n"));
+    assert_eq!(split_terminator("a\r"), ("a", "\r"));
+    assert_eq!(split_terminator("a"), ("a", ""));
+}
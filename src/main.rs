@@ -9,6 +9,8 @@ extern crate getopts;
 use getopts::Options;
 #[cfg(feature = "bin")]
 use std::env;
+#[cfg(feature = "bin")]
+use std::io::{self, BufWriter, Write};
 
 #[cfg(not(feature = "bin"))]
 fn main() {
@@ -22,9 +24,11 @@ fn main() {
 
     let mut opts = Options::new();
     opts.optopt("s", "split", "", "char|word|line");
+    opts.optopt("", "color", "", "auto|always|never");
+    opts.optflag("", "ignore-line-endings", "treat CRLF, LF and CR line endings as equal");
     let matches = match opts.parse(&args[1..]) {
         Ok(m) => m,
-        Err(f) => panic!(f.to_string()),
+        Err(f) => panic!("{}", f),
     };
 
     let split = match matches.opt_str("s") {
@@ -34,12 +38,34 @@ fn main() {
         _ => " ",
     };
 
+    let color = match matches.opt_str("color") {
+        Some(ref x) if x == "always" => true,
+        Some(ref x) if x == "never" => false,
+        _ => difference::use_color(),
+    };
+
     if matches.free.len() > 1 {
-        let ch = difference::Changeset::new(&matches.free[0], &matches.free[1], split);
-        println!("{}", ch);
+        let (orig, edit) = (&matches.free[0], &matches.free[1]);
+        let options = difference::Options {
+            ignore_line_endings: matches.opt_present("ignore-line-endings"),
+            ..difference::Options::default()
+        };
+        let ch = difference::Changeset::with_options(orig, edit, split, &options);
+        let stdout = io::stdout();
+        let mut out = BufWriter::new(stdout.lock());
+        if options.ignore_line_endings {
+            if let Some((before, after)) = difference::LineEnding::changed(orig, edit) {
+                writeln!(out, "line endings changed from {} to {}", before, after).unwrap();
+            }
+        }
+        let result = if color {
+            ch.write_to(&mut out)
+        } else {
+            ch.write_plain(&mut out)
+        };
+        result.and_then(|_| writeln!(out)).and_then(|_| out.flush()).unwrap();
     } else {
         print!("{}", opts.usage(&format!("Usage: {} [options]", program)));
-        return;
     };
 
 
@@ -0,0 +1,134 @@
+use super::{Changeset, Difference};
+use std::fmt;
+
+/// A `Changeset` rendered as Markdown, created by `Changeset::markdown`
+pub struct Markdown<'a> {
+    changeset: &'a Changeset,
+}
+
+impl Changeset {
+    /// Renders the changeset as Markdown, e.g. for comments on GitHub or GitLab.
+    ///
+    /// Line-level changesets (split by `"\n"` or tokenized by `Lines`) are
+    /// printed as a fenced `diff` code block, with every line prefixed by `+`,
+    /// `-` or a space.
+    /// All other changesets are printed inline, with removals struck through
+    /// as `~~...~~` and additions in bold as `**...**`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::Changeset;
+    ///
+    /// let changeset = Changeset::new("a\nb", "a\nc", "\n");
+    /// assert_eq!(changeset.markdown().to_string(), "```diff\n a\n-b\n+c\n```\n");
+    ///
+    /// let changeset = Changeset::new("Diffs are awesome", "Diffs are cool", " ");
+    /// assert_eq!(changeset.markdown().to_string(), "Diffs are ~~awesome~~ **cool**");
+    /// ```
+    pub fn markdown<'a>(&'a self) -> Markdown<'a> {
+        Markdown { changeset: self }
+    }
+}
+
+// escapes the characters that would otherwise be taken as Markdown formatting
+struct Escaped<'a>(&'a str);
+
+impl<'a> fmt::Display for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' | '`' | '*' | '_' | '~' | '[' | ']' | '<' | '>' => write!(f, "\\{}", c)?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// the longest run of backticks in the changeset, which the code fence has to exceed
+fn longest_backtick_run(changeset: &Changeset) -> usize {
+    let mut longest = 0;
+    for d in &changeset.diffs {
+        let x = match *d {
+            Difference::Same(ref x) | Difference::Add(ref x) | Difference::Rem(ref x) => x,
+        };
+        let mut run = 0;
+        for c in x.chars() {
+            run = if c == '`' { run + 1 } else { 0 };
+            longest = longest.max(run);
+        }
+    }
+    longest
+}
+
+// writes `x` wrapped in `delimiter`, leaving its leading and trailing
+// whitespace outside, since Markdown ignores delimiters next to whitespace
+// on the inside; text without anything but whitespace is written as is
+fn write_delimited(f: &mut fmt::Formatter, x: &str, delimiter: &str) -> fmt::Result {
+    let inner = x.trim();
+    if inner.is_empty() {
+        return write!(f, "{}", x);
+    }
+    let start = x.len() - x.trim_start().len();
+    let end = start + inner.len();
+    write!(f, "{}{}{}{}{}", &x[..start], delimiter, Escaped(inner), delimiter, &x[end..])
+}
+
+impl<'a> fmt::Display for Markdown<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let split = &self.changeset.split;
+        if self.changeset.line_based() {
+            let fence = "`".repeat(longest_backtick_run(self.changeset).max(2) + 1);
+            write!(f, "{}diff\n{}{}\n", fence, self.changeset.plain(), fence)
+        } else {
+            for (i, d) in self.changeset.diffs.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", Escaped(split))?;
+                }
+                match *d {
+                    Difference::Same(ref x) => write!(f, "{}", Escaped(x))?,
+                    Difference::Add(ref x) => write_delimited(f, x, "**")?,
+                    Difference::Rem(ref x) => write_delimited(f, x, "~~")?,
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_markdown() {
+    let changeset = Changeset::new(
+        "Roses are red,\nuse `difference`",
+        "Roses are red,\nuse ```difference```",
+        "\n",
+    );
+    assert_eq!(
+        changeset.markdown().to_string(),
+        "````diff\n\
+         \x20Roses are red,\n\
+         -use `difference`\n\
+         +use ```difference```\n\
+         ````\n"
+    );
+
+    let changeset = Changeset::new("a *b* c_d", "a *e* c_d", " ");
+    assert_eq!(
+        changeset.markdown().to_string(),
+        "a ~~\\*b\\*~~ **\\*e\\*** c\\_d"
+    );
+}
+
+#[test]
+fn test_markdown_boundaries() {
+    // empty changes are left out
+    let changeset = Changeset::new("a b", "a  b", " ");
+    assert_eq!(changeset.markdown().to_string(), "a  b");
+
+    // surrounding whitespace is moved outside of the delimiters
+    let changeset = Changeset::new("x", "x foo ", "");
+    assert_eq!(changeset.markdown().to_string(), "x **foo** ");
+    let changeset = Changeset::new("a, b", "a b", "");
+    assert_eq!(changeset.markdown().to_string(), "a~~,~~ b");
+}
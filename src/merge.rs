@@ -1,63 +1,74 @@
-use Difference;
+use lcs::Op;
+use Kind;
 
-// merges the changes from two strings, given a common substring
-pub fn merge(orig: &str, edit: &str, common: &str, split: &str) -> Vec<Difference> {
+// merges the alignment of the token lists `a` and `b` into a list of tokens
+// and their kinds, see `Changeset::from_kinds`
+//
+// A change, that is the removals and additions between two unchanged tokens,
+// is skipped if `ignore` returns true for its removed and added tokens and the
+// unchanged tokens of `a` before and after it, if any. Its removed tokens are
+// then reported as unchanged.
+pub fn merge<'a, F>(a: &[&'a str], b: &[&'a str], ops: &[Op], ignore: F) -> Vec<(Kind, &'a str)>
+where
+    F: Fn(&[&str], &[&str], Option<&str>, Option<&str>) -> bool,
+{
     let mut ret = Vec::new();
-
-    let mut l = orig.split(split).peekable();
-    let mut r = edit.split(split).peekable();
-    let mut c = common.split(split).peekable();
-
-    // Turn empty strings into [], not [""]
-    if orig == "" {
-        l.next();
-    }
-    if edit == "" {
-        r.next();
-    }
-    if common == "" {
-        c.next();
-    }
-
-    while l.peek().is_some() || r.peek().is_some() {
-        let mut same = Vec::new();
-        while l.peek().is_some() && l.peek() == c.peek() && r.peek() == c.peek() {
-            same.push(l.next().unwrap());
-            r.next();
-            c.next();
-        }
-        if !same.is_empty() {
-            let joined = same.join(split);
-            if split != "" || joined != "" {
-                ret.push(Difference::Same(joined));
-            }
+    let mut iter = ops.iter().peekable();
+    while iter.peek().is_some() {
+        while let Some(&&Op::Same(i, _)) = iter.peek() {
+            ret.push((Kind::Same, a[i]));
+            iter.next();
         }
 
         let mut rem = Vec::new();
-        while l.peek().is_some() && l.peek() != c.peek() {
-            rem.push(l.next().unwrap());
-        }
-        if !rem.is_empty() {
-            ret.push(Difference::Rem(rem.join(split)));
-        }
-
         let mut add = Vec::new();
-        while r.peek().is_some() && r.peek() != c.peek() {
-            add.push(r.next().unwrap());
+        loop {
+            match iter.peek() {
+                Some(&&Op::Rem(i)) => rem.push(a[i]),
+                Some(&&Op::Add(j)) => add.push(b[j]),
+                _ => break,
+            }
+            iter.next();
+        }
+        if rem.is_empty() && add.is_empty() {
+            continue;
         }
-        if !add.is_empty() {
-            ret.push(Difference::Add(add.join(split)));
+        let prev = match ret.last() {
+            Some(&(Kind::Same, x)) => Some(x),
+            _ => None,
+        };
+        let next = match iter.peek() {
+            Some(&&Op::Same(i, _)) => Some(a[i]),
+            _ => None,
+        };
+        if ignore(&rem, &add, prev, next) {
+            ret.extend(rem.into_iter().map(|x| (Kind::Same, x)));
+            continue;
         }
+
+        ret.extend(rem.into_iter().map(|x| (Kind::Rem, x)));
+        ret.extend(add.into_iter().map(|x| (Kind::Add, x)));
     }
 
     ret
 }
 
+#[cfg(test)]
+use Difference;
+
+#[cfg(test)]
+fn merge_str(orig: &str, edit: &str, split: &str) -> Vec<Difference> {
+    use lcs::{lcs, split_tokens};
+
+    let a = split_tokens(orig, split);
+    let b = split_tokens(edit, split);
+    ::join(&merge(&a, &b, &lcs(&a, &b), |_, _, _, _| false), split, false).0
+}
 
 #[test]
 fn test_merge() {
     assert_eq!(
-        merge("testa", "tost", "tst", ""),
+        merge_str("testa", "tost", ""),
         vec![
             Difference::Same("t".to_string()),
             Difference::Rem("e".to_string()),
@@ -68,12 +79,12 @@ fn test_merge() {
     );
 
     assert_eq!(
-        merge("", "a", "", ""),
+        merge_str("", "a", ""),
         vec![Difference::Add("a".to_string())]
     );
 
     assert_eq!(
-        merge("a\nb", "a\n\nb", "a\nb", "\n"),
+        merge_str("a\nb", "a\n\nb", "\n"),
         vec![
             Difference::Same("a".to_string()),
             Difference::Add("".to_string()),
@@ -82,7 +93,7 @@ fn test_merge() {
     );
 
     assert_eq!(
-        merge("a\n", "c\n", "\n", "\n"),
+        merge_str("a\n", "c\n", "\n"),
         vec![
             Difference::Rem("a".to_string()),
             Difference::Add("c".to_string()),
@@ -90,3 +101,31 @@ fn test_merge() {
         ]
     );
 }
+
+#[test]
+fn test_merge_ignore() {
+    let a = ["a", "", "b", "c"];
+    let b = ["a", "b", "", "d"];
+    let ops = [
+        Op::Same(0, 0),
+        Op::Rem(1),
+        Op::Same(2, 1),
+        Op::Add(2),
+        Op::Rem(3),
+        Op::Add(3),
+    ];
+    let blank = |rem: &[&str], add: &[&str], _: Option<&str>, _: Option<&str>| {
+        rem.iter().chain(add).all(|x| x.is_empty())
+    };
+
+    let tokens = merge(&a, &b, &ops, blank);
+    assert_eq!(tokens.iter().filter(|t| t.0 != Kind::Same).count(), 3);
+    assert_eq!(
+        ::join(&tokens, "\n", false).0,
+        vec![
+            Difference::Same("a\n\nb".to_string()),
+            Difference::Rem("c".to_string()),
+            Difference::Add("\nd".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,176 @@
+use super::{Changeset, Difference};
+use lcs::{lcs, strsplit, Op};
+
+#[cfg(test)]
+use style::Theme;
+
+// blocks with fewer alphanumeric characters are not considered moved, so that
+// e.g. a lone closing brace is not matched with an unrelated one, like git
+const MIN_MOVE_CHARS: usize = 20;
+
+/// A block of text that was removed in one place and added in another,
+/// see `Changeset::moves`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Move {
+    /// The index of the `Difference::Rem` in `diffs` that removed the block
+    pub rem: usize,
+    /// The index of the `Difference::Add` in `diffs` that added it again
+    pub add: usize,
+    /// The similarity of the removed and added text, from `0.0` to `1.0`
+    pub similarity: f64,
+}
+
+impl Changeset {
+    /// Detects blocks of text that were moved, i.e. removals and additions
+    /// with identical or similar content.
+    ///
+    /// Tokens are compared with leading and trailing whitespace trimmed, so that
+    /// re-indented blocks are detected as well. A removal and an addition are
+    /// a move if their similarity, twice the number of tokens they have in
+    /// common divided by the total number of their tokens, is at least
+    /// `min_similarity`. Pass `1.0` to only detect blocks that were moved
+    /// without changes.
+    ///
+    /// Every removal and addition is part of at most one move, the most
+    /// similar pairs are matched first. Removals that are directly followed by
+    /// their addition are edits, not moves, and blocks with fewer than 20
+    /// alphanumeric characters are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference};
+    ///
+    /// let changeset = Changeset::new(
+    ///     "let message = String::from(\"hello\");\nfn main() {\n    run();\n}",
+    ///     "fn main() {\n    run();\n}\n    let message = String::from(\"hello\");",
+    ///     "\n",
+    /// );
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Rem("let message = String::from(\"hello\");".to_string()),
+    ///     Difference::Same("fn main() {\n    run();\n}".to_string()),
+    ///     Difference::Add("    let message = String::from(\"hello\");".to_string()),
+    /// ]);
+    ///
+    /// let moves = changeset.moves(1.0);
+    /// assert_eq!((moves[0].rem, moves[0].add), (0, 2));
+    /// ```
+    pub fn moves(&self, min_similarity: f64) -> Vec<Move> {
+        let blocks: Vec<(usize, bool, Vec<&str>)> = self
+            .diffs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, d)| match *d {
+                Difference::Same(_) => None,
+                Difference::Rem(ref x) | Difference::Add(ref x)
+                    if x.chars().filter(|c| c.is_alphanumeric()).count() < MIN_MOVE_CHARS =>
+                {
+                    None
+                }
+                Difference::Rem(ref x) => Some((i, false, tokens(x, &self.split))),
+                Difference::Add(ref x) => Some((i, true, tokens(x, &self.split))),
+            })
+            .collect();
+
+        let mut candidates = Vec::new();
+        for &(rem, is_add, ref a) in &blocks {
+            if is_add {
+                continue;
+            }
+            for &(add, is_add, ref b) in &blocks {
+                if !is_add || add == rem + 1 {
+                    continue;
+                }
+                let similarity = similarity(a, b);
+                if similarity >= min_similarity {
+                    candidates.push(Move { rem, add, similarity });
+                }
+            }
+        }
+        // the most similar first, in order of appearance on ties
+        candidates.sort_by(|x, y| {
+            y.similarity
+                .partial_cmp(&x.similarity)
+                .unwrap()
+                .then(x.rem.cmp(&y.rem))
+                .then(x.add.cmp(&y.add))
+        });
+
+        let mut ret: Vec<Move> = Vec::new();
+        for m in candidates {
+            if ret.iter().all(|x| x.rem != m.rem && x.add != m.add) {
+                ret.push(m);
+            }
+        }
+        ret.sort_by_key(|x| x.rem);
+        ret
+    }
+}
+
+fn tokens<'a>(x: &'a str, split: &str) -> Vec<&'a str> {
+    strsplit(x, split).into_iter().map(|x| x.trim()).collect()
+}
+
+// the ratio of tokens that `a` and `b` have in common
+fn similarity(a: &[&str], b: &[&str]) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let same = lcs(a, b)
+        .iter()
+        .filter(|op| matches!(op, Op::Same(_, _)))
+        .count();
+    2.0 * same as f64 / (a.len() + b.len()) as f64
+}
+
+#[test]
+fn test_moves() {
+    let orig = "use std::io;\nlet a = String::from(\"alpha\");\nlet b = String::from(\"beta\");\n\
+                fn main() {\n    run();\n    stop();\n}";
+    let edit = "use std::io;\nfn main() {\n    run();\n    stop();\n}\n\
+                let a = String::from(\"alpha\");\nlet b = String::from(\"beta\");";
+    let changeset = Changeset::new(orig, edit, "\n");
+    assert_eq!(
+        changeset.moves(1.0),
+        vec![Move { rem: 1, add: 3, similarity: 1.0 }]
+    );
+
+    // a changed line in the moved block
+    let edit = "use std::io;\nfn main() {\n    run();\n    stop();\n}\n\
+                let a = String::from(\"alpha\");\nlet b = String::from(\"gamma\");";
+    let changeset = Changeset::new(orig, edit, "\n");
+    assert_eq!(changeset.moves(1.0), vec![]);
+    assert_eq!(
+        changeset.moves(0.5),
+        vec![Move { rem: 1, add: 3, similarity: 0.5 }]
+    );
+
+    // short blocks and edits in place are not moves
+    let changeset = Changeset::new("a\nb\nc", "c\na\nb", "\n");
+    assert_eq!(changeset.moves(0.0), vec![]);
+    let changeset = Changeset::new(
+        "the quick brown fox jumps",
+        "the quick brown fox jumps over",
+        "\n",
+    );
+    assert_eq!(changeset.moves(0.0), vec![]);
+}
+
+#[test]
+fn test_color_moved() {
+    let changeset = Changeset::new(
+        "let message = String::from(\"hello\");\nfn main() {\n}",
+        "fn main() {\n}\nlet message = String::from(\"hello\");",
+        "\n",
+    );
+    let theme = Theme::default();
+    let moved = "let message = String::from(\"hello\");";
+    assert_eq!(
+        changeset.styled(&theme).color_moved().to_string(),
+        format!(
+            "{}\nfn main() {{\n}}\n{}\n",
+            theme.moved_rem.paint(moved),
+            theme.moved_add.paint(moved)
+        )
+    );
+}
@@ -0,0 +1,196 @@
+use super::{Changeset, Difference};
+use style::Theme;
+use std::fmt;
+
+/// A line-split `Changeset` rendered with line numbers, created by `Changeset::numbered`
+pub struct Numbered<'a> {
+    changeset: &'a Changeset,
+    theme: &'a Theme,
+    context: Option<usize>,
+}
+
+impl Changeset {
+    /// Renders a line-split changeset for the terminal with a gutter showing the
+    /// line numbers in the original and the edited text, using the styles of `theme`.
+    /// Changesets tokenized by `Lines` are rendered line by line as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Style, Theme};
+    ///
+    /// let theme = Theme { add: Style::new(), rem: Style::new(), ..Theme::default() };
+    /// let changeset = Changeset::new("a\nb\nc", "a\nB\nc", "\n");
+    ///
+    /// let expected = [
+    ///     "  1  1 │  a",
+    ///     "  2    │ -b",
+    ///     "     2 │ +B",
+    ///     "  3  3 │  c",
+    ///     "",
+    /// ];
+    /// assert_eq!(changeset.numbered(&theme).to_string(), expected.join("\n"));
+    /// ```
+    pub fn numbered<'a>(&'a self, theme: &'a Theme) -> Numbered<'a> {
+        Numbered {
+            changeset: self,
+            theme,
+            context: None,
+        }
+    }
+}
+
+impl<'a> Numbered<'a> {
+    /// Collapses runs of unchanged lines, only keeping `context` lines before and
+    /// after each change. The hidden lines are replaced by a single line like
+    /// `... 240 unchanged lines ...`.
+    pub fn collapse(mut self, context: usize) -> Numbered<'a> {
+        self.context = Some(context);
+        self
+    }
+}
+
+// a line of the changeset, with its line numbers in the original and edited text
+struct Line<'a> {
+    diff: &'a Difference,
+    text: &'a str,
+    old: Option<usize>,
+    new: Option<usize>,
+}
+
+fn lines<'a>(changeset: &'a Changeset) -> Vec<Line<'a>> {
+    let mut ret = Vec::new();
+    let (mut old, mut new) = (0, 0);
+    for (i, d) in changeset.diffs.iter().enumerate() {
+        for text in changeset.split_lines(i) {
+            let (o, n) = match *d {
+                Difference::Same(_) => (Some(old + 1), Some(new + 1)),
+                Difference::Add(_) => (None, Some(new + 1)),
+                Difference::Rem(_) => (Some(old + 1), None),
+            };
+            old = o.unwrap_or(old);
+            new = n.unwrap_or(new);
+            ret.push(Line {
+                diff: d,
+                text,
+                old: o,
+                new: n,
+            });
+        }
+    }
+    ret
+}
+
+// finds which lines are hidden when keeping `context` unchanged lines around changes
+fn hidden(lines: &[Line], context: usize) -> Vec<bool> {
+    let mut hidden = vec![true; lines.len()];
+    for (i, line) in lines.iter().enumerate() {
+        if let Difference::Same(_) = *line.diff {
+            continue;
+        }
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(lines.len());
+        for h in &mut hidden[start..end] {
+            *h = false;
+        }
+    }
+    hidden
+}
+
+impl<'a> fmt::Display for Numbered<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines = lines(self.changeset);
+        let hidden = match self.context {
+            Some(context) => hidden(&lines, context),
+            None => vec![false; lines.len()],
+        };
+        let max = lines
+            .iter()
+            .map(|l| l.old.unwrap_or(0).max(l.new.unwrap_or(0)))
+            .max()
+            .unwrap_or(0);
+        let width = max.to_string().len();
+        let number = |n: Option<usize>| match n {
+            Some(n) => format!("{:>1$}", n, width),
+            None => " ".repeat(width),
+        };
+
+        let mut skipped = 0;
+        for (line, &hide) in lines.iter().zip(&hidden) {
+            if hide {
+                skipped += 1;
+                continue;
+            }
+            write_skipped(f, skipped)?;
+            skipped = 0;
+
+            let (marker, style) = match *line.diff {
+                Difference::Same(_) => (" ", &self.theme.same),
+                Difference::Add(_) => ("+", &self.theme.add),
+                Difference::Rem(_) => ("-", &self.theme.rem),
+            };
+            writeln!(
+                f,
+                "  {}  {} │ {}",
+                number(line.old),
+                number(line.new),
+                style.paint(&format!("{}{}", marker, line.text))
+            )?;
+        }
+        write_skipped(f, skipped)
+    }
+}
+
+fn write_skipped(f: &mut fmt::Formatter, skipped: usize) -> fmt::Result {
+    match skipped {
+        0 => Ok(()),
+        1 => writeln!(f, "... 1 unchanged line ..."),
+        n => writeln!(f, "... {} unchanged lines ...", n),
+    }
+}
+
+#[test]
+fn test_numbered() {
+    let theme = Theme::default();
+    let changeset = Changeset::new("a\nb\nc", "a\nc\nd", "\n");
+    assert_eq!(
+        changeset.numbered(&theme).to_string(),
+        "  1  1 │  a\n\
+         \x20 2    │ \x1b[91m-b\x1b[0m\n\
+         \x20 3  2 │  c\n\
+         \x20    3 │ \x1b[92m+d\x1b[0m\n"
+    );
+}
+
+#[test]
+fn test_numbered_collapse() {
+    let old: Vec<String> = (1..13).map(|i| i.to_string()).collect();
+    let mut new = old.clone();
+    new[6] = "seven".to_string();
+    let changeset = Changeset::new(&old.join("\n"), &new.join("\n"), "\n");
+    let theme = Theme {
+        add: Default::default(),
+        rem: Default::default(),
+        ..Theme::default()
+    };
+
+    assert_eq!(
+        changeset.numbered(&theme).collapse(2).to_string(),
+        "... 4 unchanged lines ...\n\
+         \x20  5   5 │  5\n\
+         \x20  6   6 │  6\n\
+         \x20  7     │ -7\n\
+         \x20      7 │ +seven\n\
+         \x20  8   8 │  8\n\
+         \x20  9   9 │  9\n\
+         ... 3 unchanged lines ...\n"
+    );
+
+    assert_eq!(
+        Changeset::new("a\nb", "a\nb", "\n")
+            .numbered(&theme)
+            .collapse(3)
+            .to_string(),
+        "... 2 unchanged lines ...\n"
+    );
+}
@@ -0,0 +1,127 @@
+use super::{Changeset, Difference};
+use std::ops::Range;
+
+/// The kind of an `Opcode`, like the tags of Python's `difflib`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tag {
+    /// The tokens are the same in both texts
+    Equal,
+    /// The tokens of the original text were replaced by those of the edited text
+    Replace,
+    /// The tokens of the edited text were inserted
+    Insert,
+    /// The tokens of the original text were deleted
+    Delete,
+}
+
+/// A step that turns a range of tokens of the original text into a range of
+/// tokens of the edited text, see `Changeset::opcodes`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Opcode {
+    /// What happened to the tokens
+    pub tag: Tag,
+    /// The range of tokens in the original text, empty for `Tag::Insert`
+    pub orig: Range<usize>,
+    /// The range of tokens in the edited text, empty for `Tag::Delete`
+    pub edit: Range<usize>,
+}
+
+impl Changeset {
+    /// Describes the changeset as opcodes with token index ranges, like
+    /// `get_opcodes()` of Python's `difflib.SequenceMatcher`.
+    ///
+    /// Indices count the tokens of both texts as split by `split`, or by the
+    /// tokenizer that created the changeset. A removal
+    /// that is directly followed by an addition becomes a single `Tag::Replace`.
+    /// The ranges of consecutive opcodes are adjacent and together cover both
+    /// texts, so algorithms built on `difflib` can be ported directly.
+    ///
+    /// For changesets created with `Options`, whose ignored changes are
+    /// reported as unchanged text of the original, the ranges of the edited
+    /// text are not exact.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Opcode, Tag};
+    ///
+    /// let changeset = Changeset::new("a b c d", "a x c d e", " ");
+    /// assert_eq!(changeset.opcodes(), vec![
+    ///     Opcode { tag: Tag::Equal, orig: 0..1, edit: 0..1 },
+    ///     Opcode { tag: Tag::Replace, orig: 1..2, edit: 1..2 },
+    ///     Opcode { tag: Tag::Equal, orig: 2..4, edit: 2..4 },
+    ///     Opcode { tag: Tag::Insert, orig: 4..4, edit: 4..5 },
+    /// ]);
+    /// ```
+    pub fn opcodes(&self) -> Vec<Opcode> {
+        let len = |k: usize| self.tokens(k).len();
+
+        let mut ret = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        let mut iter = self.diffs.iter().enumerate().peekable();
+        while let Some((k, d)) = iter.next() {
+            let (tag, rem, add) = match (d, iter.peek().map(|x| x.1)) {
+                (Difference::Same(_), _) => (Tag::Equal, len(k), len(k)),
+                (Difference::Rem(_), Some(Difference::Add(_))) => {
+                    iter.next();
+                    (Tag::Replace, len(k), len(k + 1))
+                }
+                (Difference::Add(_), Some(Difference::Rem(_))) => {
+                    iter.next();
+                    (Tag::Replace, len(k + 1), len(k))
+                }
+                (Difference::Rem(_), _) => (Tag::Delete, len(k), 0),
+                (Difference::Add(_), _) => (Tag::Insert, 0, len(k)),
+            };
+            ret.push(Opcode {
+                tag,
+                orig: i..i + rem,
+                edit: j..j + add,
+            });
+            i += rem;
+            j += add;
+        }
+        ret
+    }
+}
+
+#[test]
+fn test_opcodes() {
+    let changeset = Changeset::new("qabxcd", "abycdf", "");
+    assert_eq!(
+        changeset.opcodes(),
+        vec![
+            Opcode { tag: Tag::Delete, orig: 0..1, edit: 0..0 },
+            Opcode { tag: Tag::Equal, orig: 1..3, edit: 0..2 },
+            Opcode { tag: Tag::Replace, orig: 3..4, edit: 2..3 },
+            Opcode { tag: Tag::Equal, orig: 4..6, edit: 3..5 },
+            Opcode { tag: Tag::Insert, orig: 6..6, edit: 5..6 },
+        ]
+    );
+
+    let changeset = Changeset::new("a\nb", "a\n\n\nb", "\n");
+    assert_eq!(
+        changeset.opcodes(),
+        vec![
+            Opcode { tag: Tag::Equal, orig: 0..1, edit: 0..1 },
+            Opcode { tag: Tag::Insert, orig: 1..1, edit: 1..3 },
+            Opcode { tag: Tag::Equal, orig: 1..2, edit: 3..4 },
+        ]
+    );
+
+    assert_eq!(Changeset::new("", "", "").opcodes(), vec![]);
+}
+
+#[test]
+fn test_opcodes_lines() {
+    use tokenize::Lines;
+
+    let changeset = Changeset::with_tokenizer("a\nb\n", "a\nc\n", &Lines);
+    assert_eq!(
+        changeset.opcodes(),
+        vec![
+            Opcode { tag: Tag::Equal, orig: 0..1, edit: 0..1 },
+            Opcode { tag: Tag::Replace, orig: 1..2, edit: 1..2 },
+        ]
+    );
+}
@@ -0,0 +1,528 @@
+use super::Changeset;
+use lcs::{lcs, lcs_by, split_tokens};
+use line_ending::{lines, split_terminator};
+use merge::merge;
+use std::borrow::Cow;
+
+#[cfg(test)]
+use super::Difference;
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::UnicodeNormalization;
+
+/// Defines which tokens are considered the same when diffing
+///
+/// The diff core compares the normalized forms of tokens instead of the tokens
+/// themselves, while the resulting changeset still contains the original text.
+/// `Options` implements this trait for the built-in comparison options, and so
+/// does every `Fn(&str) -> String`.
+///
+/// # Examples
+///
+/// Treat log lines as equal if they only differ in their timestamp:
+///
+/// ```
+/// use difference::{Changeset, Normalizer};
+/// use std::borrow::Cow;
+///
+/// struct IgnoreTimestamps;
+///
+/// impl Normalizer for IgnoreTimestamps {
+///     fn normalize<'a>(&self, token: &'a str) -> Cow<'a, str> {
+///         // strip a leading "[hh:mm:ss] "
+///         match token.find("] ") {
+///             Some(i) if token.starts_with('[') => Cow::Borrowed(&token[i + 2..]),
+///             _ => Cow::Borrowed(token),
+///         }
+///     }
+/// }
+///
+/// let changeset = Changeset::with_normalizer(
+///     "[10:00:01] started\n[10:00:02] done",
+///     "[11:30:00] started\n[11:30:05] failed",
+///     "\n",
+///     &IgnoreTimestamps,
+/// );
+/// assert_eq!(changeset.distance, 2);
+/// ```
+pub trait Normalizer {
+    /// Returns the form of `token` that is used for comparisons. Tokens are
+    /// considered the same if their normalized forms are equal.
+    fn normalize<'a>(&self, token: &'a str) -> Cow<'a, str>;
+
+    /// Whether a change, that is the tokens removed and added between two unchanged
+    /// tokens, should be ignored. Ignored changes are not counted in the edit
+    /// distance, their removed tokens are reported as unchanged and their added
+    /// tokens are left out. By default, no change is ignored.
+    fn ignores(&self, rem: &[&str], add: &[&str]) -> bool {
+        let _ = (rem, add);
+        false
+    }
+}
+
+impl<F> Normalizer for F
+where
+    F: Fn(&str) -> String,
+{
+    fn normalize<'a>(&self, token: &'a str) -> Cow<'a, str> {
+        Cow::Owned(self(token))
+    }
+}
+
+/// How whitespace is treated when comparing tokens
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Whitespace {
+    /// Whitespace has to match exactly
+    #[default]
+    Exact,
+    /// Ignore changes in the amount of whitespace, like `diff -b`.
+    /// Runs of whitespace compare equal to a single space, and trailing
+    /// whitespace is ignored.
+    IgnoreAmount,
+    /// Ignore all whitespace, like `diff -w`
+    IgnoreAll,
+}
+
+/// How letter case is treated when comparing tokens
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Case {
+    /// Letter case has to match exactly
+    #[default]
+    Sensitive,
+    /// Ignore the case of ASCII letters
+    IgnoreAscii,
+    /// Ignore the case of all Unicode letters. This compares the lowercase of the
+    /// uppercase of tokens, so that e.g. `"ß"` and `"SS"` are equal.
+    IgnoreUnicode,
+}
+
+/// A Unicode normalization form, see <https://unicode.org/reports/tr15/>
+///
+/// Tokens are only normalized with the `unicode-normalization` feature, see
+/// `Options::normalization`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Normalization {
+    /// Canonical decomposition followed by canonical composition
+    Nfc,
+    /// Canonical decomposition
+    Nfd,
+    /// Compatibility decomposition followed by canonical composition
+    Nfkc,
+    /// Compatibility decomposition
+    Nfkd,
+}
+
+/// Options that change how tokens are compared, see `Changeset::with_options`
+///
+/// These options only affect which tokens are considered the same. The
+/// `Difference`s of the resulting changeset still contain the original text,
+/// where unchanged text is taken from the original string.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Options {
+    /// How whitespace is compared. Unless splitting by lines, changes that only
+    /// remove or add whitespace tokens, or empty tokens between repeated
+    /// separators, are ignored accordingly.
+    pub whitespace: Whitespace,
+    /// How letter case is compared
+    pub case: Case,
+    /// The Unicode normalization form tokens are converted to before comparing them,
+    /// so that e.g. precomposed and decomposed accents are equal.
+    ///
+    /// This requires the `unicode-normalization` feature. Without it, tokens
+    /// are compared without normalizing them.
+    pub normalization: Option<Normalization>,
+    /// Ignore changes that only add or remove blank tokens (usually lines),
+    /// like `diff --ignore-blank-lines`. Removed blank tokens of such changes
+    /// are reported as unchanged, added blank tokens are left out.
+    pub ignore_blank_lines: bool,
+    /// Treat `"\r\n"`, `"\n"` and `"\r"` as the same line terminator.
+    ///
+    /// When splitting by `"\n"`, the texts are split into lines at any of these
+    /// terminators. The lines keep their terminators, so the changeset has an
+    /// empty `split`, but it is still rendered line by line. With other splits,
+    /// the terminators inside tokens are compared as `"\n"`, and a trailing
+    /// `"\r"` is ignored.
+    /// Use `LineEnding::changed` to report converted line endings separately.
+    pub ignore_line_endings: bool,
+}
+
+impl Normalizer for Options {
+    fn normalize<'a>(&self, token: &'a str) -> Cow<'a, str> {
+        #[allow(unused_mut)]
+        let mut token = Cow::Borrowed(token);
+
+        if self.ignore_line_endings && token.contains('\r') {
+            let x = token.strip_suffix('\r').unwrap_or(&token);
+            token = Cow::Owned(x.replace("\r\n", "\n").replace('\r', "\n"));
+        }
+
+        #[cfg(feature = "unicode-normalization")]
+        {
+            token = match self.normalization {
+                None => token,
+                Some(Normalization::Nfc) => Cow::Owned(token.nfc().collect()),
+                Some(Normalization::Nfd) => Cow::Owned(token.nfd().collect()),
+                Some(Normalization::Nfkc) => Cow::Owned(token.nfkc().collect()),
+                Some(Normalization::Nfkd) => Cow::Owned(token.nfkd().collect()),
+            };
+        }
+
+        let token = match self.case {
+            Case::Sensitive => token,
+            Case::IgnoreAscii => {
+                if token.bytes().any(|b| b.is_ascii_uppercase()) {
+                    Cow::Owned(token.to_ascii_lowercase())
+                } else {
+                    token
+                }
+            }
+            Case::IgnoreUnicode => Cow::Owned(token.to_uppercase().to_lowercase()),
+        };
+
+        match self.whitespace {
+            Whitespace::Exact => token,
+            Whitespace::IgnoreAll => {
+                if token.contains(char::is_whitespace) {
+                    Cow::Owned(token.chars().filter(|c| !c.is_whitespace()).collect())
+                } else {
+                    token
+                }
+            }
+            Whitespace::IgnoreAmount => {
+                let mut ret = String::with_capacity(token.len());
+                let mut space = false;
+                for c in token.trim_end().chars() {
+                    if c.is_whitespace() {
+                        space = true;
+                    } else {
+                        if space {
+                            ret.push(' ');
+                            space = false;
+                        }
+                        ret.push(c);
+                    }
+                }
+                Cow::Owned(ret)
+            }
+        }
+    }
+
+    fn ignores(&self, rem: &[&str], add: &[&str]) -> bool {
+        self.ignore_blank_lines && rem.iter().chain(add).all(|x| x.trim().is_empty())
+    }
+}
+
+impl Options {
+    // whether a change between the unchanged tokens `prev` and `next` only
+    // removes and adds whitespace within a line, which `whitespace` ignores
+    //
+    // Tokens are only normalized one by one, so a change of the whitespace
+    // between tokens, e.g. of an empty token of a word split or of a space of
+    // a char split, needs the context of the change.
+    fn ignores_whitespace(
+        &self,
+        rem: &[&str],
+        add: &[&str],
+        prev: Option<&str>,
+        next: Option<&str>,
+        split: &str,
+    ) -> bool {
+        let blank = |x: &&str| !x.contains('\n') && x.trim().is_empty();
+        if split.contains('\n') || !rem.iter().chain(add).all(blank) {
+            return false;
+        }
+        match self.whitespace {
+            Whitespace::Exact => false,
+            Whitespace::IgnoreAll => true,
+            // only the amount may change, so both texts need whitespace here,
+            // or the whitespace is trailing
+            Whitespace::IgnoreAmount => {
+                let space = |x: &[&str]| x.iter().any(|x| !x.is_empty());
+                let around = (!split.is_empty() && split.trim().is_empty())
+                    || prev.is_some_and(|x| x.ends_with(|c: char| c.is_whitespace() && c != '\n'))
+                    || next.is_none_or(|x| x.starts_with(char::is_whitespace));
+                around || (space(rem) && space(add))
+            }
+        }
+    }
+}
+
+impl Changeset {
+    /// Calculates the changeset for two given strings like `Changeset::new`,
+    /// but compares tokens according to `options`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference, Options, Whitespace};
+    ///
+    /// let options = Options {
+    ///     whitespace: Whitespace::IgnoreAmount,
+    ///     ..Options::default()
+    /// };
+    /// let changeset = Changeset::with_options(
+    ///     "fn main() {\n  println!()\n}",
+    ///     "fn main() {\n    println!()  \n}\n",
+    ///     "\n",
+    ///     &options,
+    /// );
+    ///
+    /// assert_eq!(changeset.distance, 1);
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("fn main() {\n  println!()\n}".to_string()),
+    ///     Difference::Add("".to_string()),
+    /// ]);
+    /// ```
+    pub fn with_options(orig: &str, edit: &str, split: &str, options: &Options) -> Changeset {
+        if options.ignore_line_endings && split == "\n" {
+            return Changeset::lines_ignoring_endings(orig, edit, options);
+        }
+        Changeset::normalized(orig, edit, split, options, |rem, add, prev, next| {
+            options.ignores(rem, add) || options.ignores_whitespace(rem, add, prev, next, split)
+        })
+    }
+
+    // calculates the changeset of the lines of two texts, which may end in any
+    // line terminator, comparing the terminators as equal
+    fn lines_ignoring_endings(orig: &str, edit: &str, options: &Options) -> Changeset {
+        let a = lines(orig);
+        let b = lines(edit);
+        let key = |line: &&str| {
+            let (text, end) = split_terminator(line);
+            let mut key = options.normalize(text).into_owned();
+            if !end.is_empty() {
+                key.push('\n');
+            }
+            key
+        };
+        let ka: Vec<String> = a.iter().map(&key).collect();
+        let kb: Vec<String> = b.iter().map(&key).collect();
+
+        let tokens = merge(&a, &b, &lcs(&ka, &kb), |rem, add, _, _| options.ignores(rem, add));
+        let mut changeset = Changeset::from_kinds(&tokens, "", true);
+        changeset.lines = true;
+        changeset
+    }
+
+    /// Calculates the changeset for two given strings like `Changeset::new`,
+    /// but compares tokens by their form normalized by `normalizer`.
+    /// See `Normalizer` for an example.
+    pub fn with_normalizer<N: Normalizer + ?Sized>(
+        orig: &str,
+        edit: &str,
+        split: &str,
+        normalizer: &N,
+    ) -> Changeset {
+        Changeset::normalized(orig, edit, split, normalizer, |rem, add, _, _| normalizer.ignores(rem, add))
+    }
+
+    // calculates the changeset for tokens normalized by `normalizer`, skipping
+    // the changes `ignore` returns true for, see `merge`
+    fn normalized<N, F>(orig: &str, edit: &str, split: &str, normalizer: &N, ignore: F) -> Changeset
+    where
+        N: Normalizer + ?Sized,
+        F: Fn(&[&str], &[&str], Option<&str>, Option<&str>) -> bool,
+    {
+        let a = split_tokens(orig, split);
+        let b = split_tokens(edit, split);
+        Changeset::from_kinds(&merge(&a, &b, &lcs_by(&a, &b, normalizer), ignore), split, false)
+    }
+}
+
+#[test]
+fn test_whitespace() {
+    let options = Options {
+        whitespace: Whitespace::IgnoreAll,
+        ..Options::default()
+    };
+    let changeset = Changeset::with_options("a b\tc\nd e", "ab c\n d  f", "\n", &options);
+    assert_eq!(changeset.distance, 2);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("a b\tc".to_string()),
+            Difference::Rem("d e".to_string()),
+            Difference::Add(" d  f".to_string()),
+        ]
+    );
+
+    let options = Options {
+        whitespace: Whitespace::IgnoreAmount,
+        ..Options::default()
+    };
+    let changeset = Changeset::with_options("a b\nc d\ne", "a  b \ncd\n e", "\n", &options);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("a b".to_string()),
+            Difference::Rem("c d\ne".to_string()),
+            Difference::Add("cd\n e".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_whitespace_between_tokens() {
+    let amount = Options {
+        whitespace: Whitespace::IgnoreAmount,
+        ..Options::default()
+    };
+    let all = Options {
+        whitespace: Whitespace::IgnoreAll,
+        ..Options::default()
+    };
+
+    // an empty token between two spaces of a word split
+    for options in &[amount, all] {
+        let changeset = Changeset::with_options("a b c", "a  b c", " ", options);
+        assert_eq!(changeset.diffs, vec![Difference::Same("a b c".to_string())]);
+        assert_eq!(changeset.distance, 0);
+    }
+
+    // a space of a char split
+    let changeset = Changeset::with_options("ab", "a b", "", &all);
+    assert_eq!(changeset.diffs, vec![Difference::Same("ab".to_string())]);
+    assert_eq!(changeset.distance, 0);
+    assert_eq!(Changeset::with_options("ab", "a b", "", &amount).distance, 1);
+    assert_eq!(Changeset::with_options("a b", "ab", "", &amount).distance, 1);
+    assert_eq!(Changeset::with_options("a b", "a  b", "", &amount).distance, 0);
+    assert_eq!(Changeset::with_options("a b", "a\tb", "", &amount).distance, 0);
+    assert_eq!(Changeset::with_options("a b", "a b  ", "", &amount).distance, 0);
+    assert_eq!(Changeset::with_options("a", " a", "", &amount).distance, 1);
+
+    // line breaks are not whitespace within a line
+    assert_eq!(Changeset::with_options("ab", "a\nb", "", &all).distance, 1);
+    assert_eq!(Changeset::with_options("a\nb", "a\n\nb", "\n", &all).distance, 1);
+}
+
+#[test]
+fn test_ignore_blank_lines() {
+    let options = Options {
+        ignore_blank_lines: true,
+        ..Options::default()
+    };
+    let changeset = Changeset::with_options("a\n\nb\nc", "a\nb\n \nd", "\n", &options);
+    assert_eq!(changeset.distance, 3);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("a\n\nb".to_string()),
+            Difference::Rem("c".to_string()),
+            Difference::Add(" \nd".to_string()),
+        ]
+    );
+
+    let changeset = Changeset::with_options("a\nb", "a\n\n\nb\n", "\n", &options);
+    assert_eq!(changeset.distance, 0);
+    assert_eq!(changeset.diffs, vec![Difference::Same("a\nb".to_string())]);
+}
+
+#[test]
+fn test_case() {
+    let options = Options {
+        case: Case::IgnoreAscii,
+        ..Options::default()
+    };
+    let changeset = Changeset::with_options("Hello World Straße", "hello WORLD STRASSE", " ", &options);
+    assert_eq!(changeset.distance, 2);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("Hello World".to_string()),
+            Difference::Rem("Straße".to_string()),
+            Difference::Add("STRASSE".to_string()),
+        ]
+    );
+
+    let options = Options {
+        case: Case::IgnoreUnicode,
+        ..Options::default()
+    };
+    let changeset = Changeset::with_options("Hello Straße ΣΑΣ", "hello STRASSE σας", " ", &options);
+    assert_eq!(changeset.distance, 0);
+    assert_eq!(
+        changeset.diffs,
+        vec![Difference::Same("Hello Straße ΣΑΣ".to_string())]
+    );
+}
+
+#[cfg(not(feature = "unicode-normalization"))]
+#[test]
+fn test_normalization_disabled() {
+    // the field is always available, but only takes effect with the feature
+    let options = Options {
+        normalization: Some(Normalization::Nfc),
+        ..Options::default()
+    };
+    let changeset = Changeset::with_options("caf\u{e9} au lait", "cafe\u{301} au lait", " ", &options);
+    assert_eq!(changeset.distance, 2);
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn test_normalization() {
+    let options = Options {
+        normalization: Some(Normalization::Nfc),
+        ..Options::default()
+    };
+    // "café" with a precomposed and with a combining accent
+    let changeset = Changeset::with_options("caf\u{e9} au lait", "cafe\u{301} au lait", " ", &options);
+    assert_eq!(changeset.distance, 0);
+
+    let changeset = Changeset::with_options("\u{fb01}ne", "fine", "", &options);
+    assert_eq!(changeset.distance, 3);
+
+    let options = Options {
+        normalization: Some(Normalization::Nfkc),
+        case: Case::IgnoreUnicode,
+        ..Options::default()
+    };
+    let changeset = Changeset::with_options("\u{fb01}ne CAF\u{c9}", "fine cafe\u{301}", " ", &options);
+    assert_eq!(changeset.distance, 0);
+}
+
+#[test]
+fn test_ignore_line_endings() {
+    let options = Options {
+        ignore_line_endings: true,
+        ..Options::default()
+    };
+    let changeset = Changeset::with_options("a\r\nb\r\nc\r\n", "a\nb\nd\n", "\n", &options);
+    assert_eq!(changeset.distance, 2);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("a\r\nb\r\n".to_string()),
+            Difference::Rem("c\r\n".to_string()),
+            Difference::Add("d\n".to_string()),
+        ]
+    );
+
+    // classic Mac OS line endings
+    let changeset = Changeset::with_options("a\rb\rc", "a\nb\nd", "\n", &options);
+    assert_eq!(changeset.distance, 2);
+    assert_eq!(
+        changeset.plain().to_string(),
+        " a\n b\n-c\n\\ No newline at end of file\n+d\n\\ No newline at end of file\n"
+    );
+
+    // a missing final line break is still a change
+    let changeset = Changeset::with_options("a\rb\r", "a\nb", "\n", &options);
+    assert_eq!(changeset.distance, 2);
+
+    let changeset = Changeset::with_options("a\rb c", "a\r\nb c", " ", &options);
+    assert_eq!(changeset.distance, 0);
+}
+
+#[test]
+fn test_normalizer_closure() {
+    let digits = |x: &str| x.replace(|c: char| c.is_ascii_digit(), "0");
+    let changeset = Changeset::with_normalizer("id 12 of 34", "id 56 of 78 total", " ", &digits);
+    assert_eq!(changeset.distance, 1);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("id 12 of 34".to_string()),
+            Difference::Add("total".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,130 @@
+use super::Changeset;
+use lcs::{backtrack, split_tokens, Op};
+use merge::merge;
+use rayon::prelude::*;
+
+// the number of rows and columns of a tile of the table
+const TILE: usize = 256;
+
+impl Changeset {
+    /// Calculates the changeset for two given strings like `Changeset::new`,
+    /// using all threads of the `rayon` thread pool.
+    ///
+    /// The table of longest common subsequences is divided into tiles, and
+    /// all tiles on an anti-diagonal, which only depend on the tiles above and
+    /// to the left of them, are computed in parallel. Since the table is exactly
+    /// the same as in `Changeset::new`, so is the resulting changeset.
+    /// This only pays off for large inputs, small ones are diffed sequentially.
+    ///
+    /// This requires the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::Changeset;
+    ///
+    /// let orig: Vec<String> = (0..2000).map(|i| format!("line {}", i)).collect();
+    /// let edit: Vec<String> = (0..2000).map(|i| format!("line {}", i * 3 / 2)).collect();
+    /// let (orig, edit) = (orig.join("\n"), edit.join("\n"));
+    ///
+    /// assert_eq!(
+    ///     Changeset::new_parallel(&orig, &edit, "\n"),
+    ///     Changeset::new(&orig, &edit, "\n")
+    /// );
+    /// ```
+    pub fn new_parallel(orig: &str, edit: &str, split: &str) -> Changeset {
+        let a = split_tokens(orig, split);
+        let b = split_tokens(edit, split);
+        Changeset::from_kinds(&merge(&a, &b, &lcs_parallel(&a, &b), |_, _, _, _| false), split, false)
+    }
+}
+
+// finds the longest common subsequence of `a` and `b` like `lcs`, computing
+// the tiles of each anti-diagonal of the table in parallel
+#[allow(non_snake_case)]
+pub fn lcs_parallel<T: PartialEq + Sync>(a: &[T], b: &[T]) -> Vec<Op> {
+    let N = a.len();
+    let M = b.len();
+    if N <= TILE || M <= TILE {
+        return ::lcs::lcs(a, b);
+    }
+
+    let rows = N.div_ceil(TILE);
+    let cols = M.div_ceil(TILE);
+    let width = |tj: usize| TILE.min(M - tj * TILE);
+
+    // tiles[ti * cols + tj] holds the rows of the tile, each `width(tj)` long
+    let mut tiles: Vec<Vec<usize>> = vec![Vec::new(); rows * cols];
+    for d in 0..rows + cols - 1 {
+        let first = if d >= cols { d - cols + 1 } else { 0 };
+        let last = d.min(rows - 1);
+        let done: Vec<(usize, Vec<usize>)> = (first..last + 1)
+            .into_par_iter()
+            .map(|ti| {
+                let tj = d - ti;
+                (ti * cols + tj, tile(a, b, &tiles, cols, ti, tj))
+            })
+            .collect();
+        for (k, t) in done {
+            tiles[k] = t;
+        }
+    }
+
+    backtrack(a, b, |i, j| {
+        let (ti, tj) = (i / TILE, j / TILE);
+        tiles[ti * cols + tj][(i % TILE) * width(tj) + j % TILE]
+    })
+}
+
+// computes the tile at row `ti` and column `tj` from the tiles above and to
+// the left of it
+fn tile<T: PartialEq>(a: &[T], b: &[T], tiles: &[Vec<usize>], cols: usize, ti: usize, tj: usize) -> Vec<usize> {
+    let (r0, c0) = (ti * TILE, tj * TILE);
+    let h = TILE.min(a.len() - r0);
+    let w = TILE.min(b.len() - c0);
+
+    // the tile padded by the last row of the tile above, and the last column
+    // of the tile to the left, or zeros at the edges of the table
+    let mut t = vec![0; (h + 1) * (w + 1)];
+    if ti > 0 {
+        let above = &tiles[(ti - 1) * cols + tj];
+        t[1..w + 1].copy_from_slice(&above[(TILE - 1) * w..]);
+    }
+    if tj > 0 {
+        let left = &tiles[ti * cols + tj - 1];
+        for i in 0..h {
+            t[(i + 1) * (w + 1)] = left[i * TILE + TILE - 1];
+        }
+    }
+    if ti > 0 && tj > 0 {
+        t[0] = tiles[(ti - 1) * cols + tj - 1][TILE * TILE - 1];
+    }
+
+    for i in 1..h + 1 {
+        for j in 1..w + 1 {
+            t[i * (w + 1) + j] = if a[r0 + i - 1] == b[c0 + j - 1] {
+                t[(i - 1) * (w + 1) + j - 1] + 1
+            } else {
+                t[i * (w + 1) + j - 1].max(t[(i - 1) * (w + 1) + j])
+            };
+        }
+    }
+
+    let mut ret = Vec::with_capacity(h * w);
+    for i in 1..h + 1 {
+        ret.extend_from_slice(&t[i * (w + 1) + 1..(i + 1) * (w + 1)]);
+    }
+    ret
+}
+
+#[test]
+fn test_lcs_parallel() {
+    // sizes that are not multiples of the tile size, with repeated tokens
+    let a: Vec<u32> = (0..700).map(|i| (i * 7919) % 13).collect();
+    let b: Vec<u32> = (0..600).map(|i| (i * 104_729) % 11).collect();
+    assert_eq!(lcs_parallel(&a, &b), ::lcs::lcs(&a, &b));
+    assert_eq!(lcs_parallel(&b, &a), ::lcs::lcs(&b, &a));
+
+    let c: Vec<u32> = (0..300).collect();
+    assert_eq!(lcs_parallel(&a, &c), ::lcs::lcs(&a, &c));
+}
@@ -0,0 +1,290 @@
+use super::{Changeset, Difference};
+use style::{Style, Theme};
+use std::fmt;
+
+/// An element of a `Refined` changeset
+#[derive(Clone, PartialEq, Debug)]
+pub enum Refinement {
+    /// A `Difference` that is not part of a replacement
+    Single(Difference),
+    /// A `Difference::Rem` directly followed by a `Difference::Add`, replaced by
+    /// the finer-grained changeset between the removed and the added text
+    Replace(Changeset),
+}
+
+/// A changeset in which every replacement carries a nested changeset,
+/// created by `Changeset::refine`
+#[derive(Clone, PartialEq, Debug)]
+pub struct Refined {
+    /// An ordered vector of `Refinement` objects, corresponding to the
+    /// differences of the refined changeset
+    pub diffs: Vec<Refinement>,
+    /// The split of the refined changeset
+    pub split: String,
+}
+
+impl Changeset {
+    /// Refines the changeset by diffing each removal that is directly followed by
+    /// an addition again, using the finer `split`. This is commonly used to
+    /// highlight the words that changed within a line-level changeset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference, Refinement};
+    ///
+    /// let changeset = Changeset::new("a\nRoses are red\nb", "a\nRoses are blue\nb", "\n");
+    /// let refined = changeset.refine(" ");
+    ///
+    /// assert_eq!(refined.diffs, vec![
+    ///     Refinement::Single(Difference::Same("a".to_string())),
+    ///     Refinement::Replace(Changeset::new("Roses are red", "Roses are blue", " ")),
+    ///     Refinement::Single(Difference::Same("b".to_string())),
+    /// ]);
+    /// ```
+    pub fn refine(&self, split: &str) -> Refined {
+        let mut diffs = Vec::new();
+        let mut iter = self.diffs.iter().peekable();
+        while let Some(d) = iter.next() {
+            if let Difference::Rem(ref rem) = *d {
+                if let Some(Difference::Add(add)) = iter.peek() {
+                    iter.next();
+                    diffs.push(Refinement::Replace(Changeset::new(rem, add, split)));
+                    continue;
+                }
+            }
+            diffs.push(Refinement::Single(d.clone()));
+        }
+        Refined {
+            diffs,
+            split: self.split.clone(),
+        }
+    }
+}
+
+// collects the removed (or added) side of a replacement, flagging the parts
+// that changed
+fn side(inner: &Changeset, added: bool) -> Vec<(&str, bool)> {
+    inner
+        .diffs
+        .iter()
+        .filter_map(|d| match *d {
+            Difference::Same(ref x) => Some((x.as_str(), false)),
+            Difference::Add(ref x) if added => Some((x.as_str(), true)),
+            Difference::Rem(ref x) if !added => Some((x.as_str(), true)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A `Refined` changeset rendered for the terminal, created by `Refined::styled`
+pub struct RefinedStyled<'a> {
+    refined: &'a Refined,
+    theme: &'a Theme,
+}
+
+impl Refined {
+    /// Renders the refined changeset for the terminal, using the styles of `theme`.
+    /// The changed parts of replacements are painted with the emphasis styles.
+    pub fn styled<'a>(&'a self, theme: &'a Theme) -> RefinedStyled<'a> {
+        RefinedStyled {
+            refined: self,
+            theme,
+        }
+    }
+
+    /// Renders the refined changeset as HTML. Removals and additions are wrapped
+    /// in `<del>` and `<ins>`, the changed parts of replacements additionally
+    /// in `<mark>`. The text is escaped, but not wrapped in any container element,
+    /// so line-level changesets are best embedded in a `<pre>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::Changeset;
+    ///
+    /// let refined = Changeset::new("x < 1", "x < 2", "\n").refine(" ");
+    /// assert_eq!(
+    ///     refined.html().to_string(),
+    ///     "<del>x &lt; <mark>1</mark></del>\n<ins>x &lt; <mark>2</mark></ins>\n"
+    /// );
+    /// ```
+    pub fn html<'a>(&'a self) -> Html<'a> {
+        Html { refined: self }
+    }
+}
+
+impl<'a> RefinedStyled<'a> {
+    fn write_side(
+        &self,
+        f: &mut fmt::Formatter,
+        inner: &Changeset,
+        added: bool,
+        style: &Style,
+        emphasis: &Style,
+    ) -> fmt::Result {
+        for (i, (x, changed)) in side(inner, added).into_iter().enumerate() {
+            if i > 0 && !inner.split.is_empty() {
+                write!(f, "{}", style.paint(&inner.split))?;
+            }
+            if !x.is_empty() {
+                write!(f, "{}", if changed { emphasis } else { style }.paint(x))?;
+            }
+        }
+        write!(f, "{}", self.refined.split)
+    }
+}
+
+impl<'a> fmt::Display for RefinedStyled<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let theme = self.theme;
+        for d in &self.refined.diffs {
+            match *d {
+                Refinement::Single(ref d) => {
+                    let (x, style) = match *d {
+                        Difference::Same(ref x) => (x, &theme.same),
+                        Difference::Add(ref x) => (x, &theme.add),
+                        Difference::Rem(ref x) => (x, &theme.rem),
+                    };
+                    write!(f, "{}{}", style.paint(x), self.refined.split)?;
+                }
+                Refinement::Replace(ref inner) => {
+                    self.write_side(f, inner, false, &theme.rem, &theme.rem_emphasis)?;
+                    self.write_side(f, inner, true, &theme.add, &theme.add_emphasis)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Refined {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.styled(&Theme::default()))
+    }
+}
+
+// escapes text for use in HTML
+struct Escaped<'a>(&'a str);
+
+impl<'a> fmt::Display for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut last = 0;
+        for (i, c) in self.0.char_indices() {
+            let entity = match c {
+                '&' => "&amp;",
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '"' => "&quot;",
+                '\'' => "&#39;",
+                _ => continue,
+            };
+            write!(f, "{}{}", &self.0[last..i], entity)?;
+            last = i + 1;
+        }
+        write!(f, "{}", &self.0[last..])
+    }
+}
+
+/// A `Refined` changeset rendered as HTML, created by `Refined::html`
+pub struct Html<'a> {
+    refined: &'a Refined,
+}
+
+impl<'a> Html<'a> {
+    fn write_side(&self, f: &mut fmt::Formatter, inner: &Changeset, added: bool) -> fmt::Result {
+        let tag = if added { "ins" } else { "del" };
+        write!(f, "<{}>", tag)?;
+        for (i, (x, changed)) in side(inner, added).into_iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", Escaped(&inner.split))?;
+            }
+            if changed && !x.is_empty() {
+                write!(f, "<mark>{}</mark>", Escaped(x))?;
+            } else {
+                write!(f, "{}", Escaped(x))?;
+            }
+        }
+        write!(f, "</{}>{}", tag, Escaped(&self.refined.split))
+    }
+}
+
+impl<'a> fmt::Display for Html<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let split = Escaped(&self.refined.split);
+        for d in &self.refined.diffs {
+            match *d {
+                Refinement::Single(Difference::Same(ref x)) => {
+                    write!(f, "{}{}", Escaped(x), split)?;
+                }
+                Refinement::Single(Difference::Add(ref x)) => {
+                    write!(f, "<ins>{}</ins>{}", Escaped(x), split)?;
+                }
+                Refinement::Single(Difference::Rem(ref x)) => {
+                    write!(f, "<del>{}</del>{}", Escaped(x), split)?;
+                }
+                Refinement::Replace(ref inner) => {
+                    self.write_side(f, inner, false)?;
+                    self.write_side(f, inner, true)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_refine() {
+    let changeset = Changeset::new(
+        "Roses are red,\nI wrote this library,\njust for you.",
+        "Roses are red,\nI wrote this documentation,\njust for you.\n(It's true).",
+        "\n",
+    );
+    let refined = changeset.refine(" ");
+
+    assert_eq!(refined.split, "\n");
+    assert_eq!(
+        refined.diffs,
+        vec![
+            Refinement::Single(Difference::Same("Roses are red,".to_string())),
+            Refinement::Replace(Changeset::new(
+                "I wrote this library,",
+                "I wrote this documentation,",
+                " ",
+            )),
+            Refinement::Single(Difference::Same("just for you.".to_string())),
+            Refinement::Single(Difference::Add("(It's true).".to_string())),
+        ]
+    );
+
+    assert_eq!(
+        refined.to_string(),
+        "Roses are red,\n\
+         \x1b[91mI wrote this\x1b[0m\x1b[91m \x1b[0m\x1b[97;41mlibrary,\x1b[0m\n\
+         \x1b[92mI wrote this\x1b[0m\x1b[92m \x1b[0m\x1b[97;42mdocumentation,\x1b[0m\n\
+         just for you.\n\
+         \x1b[92m(It's true).\x1b[0m\n"
+    );
+
+    assert_eq!(
+        refined.html().to_string(),
+        "Roses are red,\n\
+         <del>I wrote this <mark>library,</mark></del>\n\
+         <ins>I wrote this <mark>documentation,</mark></ins>\n\
+         just for you.\n\
+         <ins>(It&#39;s true).</ins>\n"
+    );
+}
+
+#[test]
+fn test_refine_chars() {
+    let refined = Changeset::new("Rust", "Dust", " ").refine("");
+    assert_eq!(
+        refined.diffs,
+        vec![Refinement::Replace(Changeset::new("Rust", "Dust", ""))]
+    );
+    assert_eq!(
+        refined.html().to_string(),
+        "<del><mark>R</mark>ust</del> <ins><mark>D</mark>ust</ins> "
+    );
+}
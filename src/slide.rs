@@ -0,0 +1,257 @@
+use super::{join, Changeset, Kind};
+
+#[cfg(test)]
+use super::Difference;
+
+impl Changeset {
+    /// Slides blocks of added or removed lines to the most readable position,
+    /// like `git diff --indent-heuristic`.
+    ///
+    /// A block that is surrounded by unchanged lines can often be moved up or
+    /// down, if its first or last line is repeated around it. For example,
+    /// inserting a function after another one can be reported starting at the
+    /// closing `}` of the previous function. This moves such blocks so that
+    /// they start and end at blank lines, or at least do not split an indented
+    /// block from its surrounding lines. The edit distance does not change.
+    ///
+    /// Only blocks that consist of additions or removals alone are moved, by
+    /// whole tokens. The heuristic is meant for changesets that are split by
+    /// lines, or into lines by `Lines`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference};
+    ///
+    /// let mut changeset = Changeset::new(
+    ///     "fn a() {\n}\n\nfn c() {\n}",
+    ///     "fn a() {\n}\n\nfn b() {\n}\n\nfn c() {\n}",
+    ///     "\n",
+    /// );
+    /// changeset.indent_heuristic();
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("fn a() {\n}\n".to_string()),
+    ///     Difference::Add("fn b() {\n}\n".to_string()),
+    ///     Difference::Same("fn c() {\n}".to_string()),
+    /// ]);
+    /// ```
+    pub fn indent_heuristic(&mut self) {
+        let tokens = self.kinds();
+        let mut kinds: Vec<Kind> = tokens.iter().map(|t| t.0).collect();
+        let text: Vec<&str> = tokens.iter().map(|t| t.1).collect();
+
+        let mut start = 0;
+        while start < kinds.len() {
+            let kind = kinds[start];
+            let mut end = start + 1;
+            while end < kinds.len() && kinds[end] == kind {
+                end += 1;
+            }
+            let pure = kind != Kind::Same
+                && (start == 0 || kinds[start - 1] == Kind::Same)
+                && (end == kinds.len() || kinds[end] == Kind::Same);
+            if pure {
+                end = slide(&mut kinds, &text, start, end);
+            }
+            start = end;
+        }
+
+        let tokens: Vec<(Kind, &str)> = kinds.into_iter().zip(text).collect();
+        let (diffs, bounds) = join(&tokens, &self.split, self.bounds.is_some());
+        self.diffs = diffs;
+        self.bounds = bounds;
+    }
+}
+
+// moves the changed block `kinds[start..end]` to its best position between the
+// unchanged tokens around it, outputs the new end of the block
+//
+// Moving a block by one token relabels the token before it and its own last
+// token, or its first token and the token after it. This is possible if both
+// tokens are equal, so the text of every position stays the same. The block
+// is never moved next to another change.
+fn slide(kinds: &mut [Kind], text: &[&str], start: usize, end: usize) -> usize {
+    let kind = kinds[start];
+    let len = end - start;
+    let free = |i: usize| i >= kinds.len() || kinds[i] == Kind::Same;
+
+    let mut top = start;
+    while top > 0 && text[top - 1] == text[top + len - 1] && (top == 1 || free(top - 2)) {
+        top -= 1;
+    }
+    let mut bottom = start;
+    while bottom + len < kinds.len()
+        && text[bottom] == text[bottom + len]
+        && free(bottom + len + 1)
+    {
+        bottom += 1;
+    }
+
+    // prefer the lowest position on ties, like git
+    let mut best = bottom;
+    let mut best_score = score(text, bottom, bottom + len);
+    for pos in top..bottom {
+        let s = score(text, pos, pos + len);
+        if s < best_score {
+            best = pos;
+            best_score = s;
+        }
+    }
+
+    for k in kinds[start..end].iter_mut() {
+        *k = Kind::Same;
+    }
+    for k in kinds[best..best + len].iter_mut() {
+        *k = kind;
+    }
+    best + len
+}
+
+// the penalty for a block spanning `text[start..end]`
+//
+// The empty token after a trailing newline is the end of the text, not a
+// blank line.
+fn score(text: &[&str], start: usize, end: usize) -> i32 {
+    let before = if start > 0 { Some(text[start - 1]) } else { None };
+    let after = match text.get(end) {
+        Some(&"") if end + 1 == text.len() => None,
+        after => after.cloned(),
+    };
+    split_score(before, Some(text[start])) + split_score(Some(text[end - 1]), after)
+}
+
+// the penalty for a block boundary between the tokens `prev` and `next`
+//
+// Boundaries after blank lines and at the start or end of the text are best.
+// Boundaries before blank lines, and boundaries between lines with different
+// indentation, which would separate a block from its opening or closing
+// line, are worse.
+fn split_score(prev: Option<&str>, next: Option<&str>) -> i32 {
+    let (prev, next) = match (prev, next) {
+        (Some(prev), Some(next)) => (prev, next),
+        _ => return 0,
+    };
+    match (indent(prev), indent(next)) {
+        (None, _) => -10,
+        (_, None) => 5,
+        (Some(p), Some(n)) if n < p => 3,
+        (Some(p), Some(n)) if n > p => 2,
+        _ => 0,
+    }
+}
+
+// the indentation of a line in columns, or `None` if it is blank
+fn indent(line: &str) -> Option<usize> {
+    let mut ret = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => ret += 1,
+            '\t' => ret += 8 - ret % 8,
+            c if c.is_whitespace() => {}
+            _ => return Some(ret),
+        }
+    }
+    None
+}
+
+#[test]
+fn test_indent_heuristic() {
+    // the insertion starts at the closing brace of the previous function
+    let mut changeset = Changeset::new(
+        "fn a() {\n    x\n}\nfn c() {\n    z\n}",
+        "fn a() {\n    x\n}\nfn b() {\n    y\n}\nfn c() {\n    z\n}",
+        "\n",
+    );
+    changeset.diffs = vec![
+        Difference::Same("fn a() {\n    x".to_string()),
+        Difference::Add("}\nfn b() {\n    y".to_string()),
+        Difference::Same("}\nfn c() {\n    z\n}".to_string()),
+    ];
+    let distance = changeset.distance;
+    changeset.indent_heuristic();
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("fn a() {\n    x\n}".to_string()),
+            Difference::Add("fn b() {\n    y\n}".to_string()),
+            Difference::Same("fn c() {\n    z\n}".to_string()),
+        ]
+    );
+    assert_eq!(changeset.distance, distance);
+
+    let mut changeset = Changeset::new("a\n\nb\n\nc", "a\n\nc", "\n");
+    changeset.diffs = vec![
+        Difference::Same("a".to_string()),
+        Difference::Rem("\nb".to_string()),
+        Difference::Same("\nc".to_string()),
+    ];
+    changeset.indent_heuristic();
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("a\n".to_string()),
+            Difference::Rem("b\n".to_string()),
+            Difference::Same("c".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_indent_heuristic_trailing_newline() {
+    // a block appended to a text ending in a newline stays at the end
+    let mut changeset = Changeset::new(
+        "if a {\n    x\n}\n",
+        "if a {\n    x\n}\nif b {\n    x\n}\n",
+        "\n",
+    );
+    changeset.indent_heuristic();
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("if a {\n    x\n}".to_string()),
+            Difference::Add("if b {\n    x\n}".to_string()),
+            Difference::Same("".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_indent_heuristic_neighbours() {
+    // blocks are not moved next to other changes
+    let mut changeset = Changeset::new("x\na\nb", "y\na\na\nb", "\n");
+    let before = changeset.clone();
+    changeset.indent_heuristic();
+    assert_eq!(changeset, before);
+}
+
+#[test]
+fn test_indent_heuristic_lines() {
+    use tokenize::Lines;
+
+    // tokenized changesets slide by whole tokens, not by characters
+    let mut changeset = Changeset::with_tokenizer(
+        "fn a() {\n}\nfn c() {\n}\n",
+        "fn a() {\n}\nfn b() {\n}\nfn c() {\n}\n",
+        &Lines,
+    );
+    changeset.diffs = vec![
+        Difference::Same("fn a() {\n".to_string()),
+        Difference::Add("}\nfn b() {\n".to_string()),
+        Difference::Same("}\nfn c() {\n}\n".to_string()),
+    ];
+    changeset.indent_heuristic();
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("fn a() {\n}\n".to_string()),
+            Difference::Add("fn b() {\n}\n".to_string()),
+            Difference::Same("fn c() {\n}\n".to_string()),
+        ]
+    );
+    assert_eq!(changeset.edit_distance(), 2);
+    assert_eq!(
+        changeset.plain().to_string(),
+        " fn a() {\n }\n+fn b() {\n+}\n fn c() {\n }\n"
+    );
+}
@@ -0,0 +1,139 @@
+use super::{Changeset, Difference};
+
+/// Statistics about a `Changeset`, created by `Changeset::stats`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stats {
+    /// The number of added tokens
+    pub added: usize,
+    /// The number of removed tokens
+    pub removed: usize,
+    /// The number of unchanged tokens
+    pub unchanged: usize,
+    /// The number of bytes of added text
+    pub added_bytes: usize,
+    /// The number of bytes of removed text
+    pub removed_bytes: usize,
+    /// The number of bytes of unchanged text
+    pub unchanged_bytes: usize,
+    /// The number of hunks, i.e. runs of changes between unchanged text
+    pub hunks: usize,
+    /// How similar both texts are, from `0.0` to `1.0`, like `ratio()` of
+    /// Python's `difflib.SequenceMatcher`: twice the number of unchanged tokens,
+    /// divided by the total number of tokens in both texts. It is `1.0` if both
+    /// texts are empty.
+    pub ratio: f64,
+}
+
+impl Changeset {
+    /// Counts the added, removed and unchanged tokens and bytes of the changeset.
+    ///
+    /// Tokens are counted like `Changeset::edit_distance` counts them, i.e. by
+    /// splitting the text of every `Difference` by `split`, or into the tokens of
+    /// the tokenizer that created the changeset, so `added + removed` always
+    /// equals the edit distance. Bytes are counted from the text of every
+    /// `Difference`, without the separators between them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::Changeset;
+    ///
+    /// let changeset = Changeset::new("The quick brown fox", "The slow brown dog", " ");
+    /// let stats = changeset.stats();
+    ///
+    /// assert_eq!((stats.added, stats.removed, stats.unchanged), (2, 2, 2));
+    /// assert_eq!(stats.added + stats.removed, changeset.edit_distance());
+    /// assert_eq!(stats.hunks, 2);
+    /// assert_eq!(stats.ratio, 0.5);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats {
+            added: 0,
+            removed: 0,
+            unchanged: 0,
+            added_bytes: 0,
+            removed_bytes: 0,
+            unchanged_bytes: 0,
+            hunks: 0,
+            ratio: 1.0,
+        };
+
+        let mut in_hunk = false;
+        for (i, d) in self.diffs.iter().enumerate() {
+            let (x, tokens, bytes) = match *d {
+                Difference::Same(ref x) => {
+                    in_hunk = false;
+                    (x, &mut stats.unchanged, &mut stats.unchanged_bytes)
+                }
+                Difference::Add(ref x) => (x, &mut stats.added, &mut stats.added_bytes),
+                Difference::Rem(ref x) => (x, &mut stats.removed, &mut stats.removed_bytes),
+            };
+            *tokens += self.tokens(i).len();
+            *bytes += x.len();
+
+            if !in_hunk && !matches!(*d, Difference::Same(_)) {
+                in_hunk = true;
+                stats.hunks += 1;
+            }
+        }
+
+        let total = 2 * stats.unchanged + stats.added + stats.removed;
+        if total > 0 {
+            stats.ratio = 2.0 * stats.unchanged as f64 / total as f64;
+        }
+        stats
+    }
+}
+
+#[test]
+fn test_stats() {
+    let changeset = Changeset::new("a\nb\nc\nd", "a\nx\nc\nd\ne", "\n");
+    assert_eq!(
+        changeset.stats(),
+        Stats {
+            added: 2,
+            removed: 1,
+            unchanged: 3,
+            added_bytes: 2,
+            removed_bytes: 1,
+            unchanged_bytes: 4,
+            hunks: 2,
+            ratio: 2.0 * 3.0 / 9.0,
+        }
+    );
+
+    let stats = Changeset::new("", "", "").stats();
+    assert_eq!((stats.hunks, stats.ratio), (0, 1.0));
+
+    let stats = Changeset::new("abc", "xyz", "").stats();
+    assert_eq!((stats.added, stats.removed, stats.hunks, stats.ratio), (3, 3, 1, 0.0));
+}
+
+#[test]
+fn test_stats_edit_distance() {
+    use tokenize::Lines;
+
+    for &(orig, edit, split) in &[("", "bcac", " "), ("a\n", "c\n", "\n"), ("a b", "", " ")] {
+        let changeset = Changeset::new(orig, edit, split);
+        let stats = changeset.stats();
+        assert_eq!(stats.added + stats.removed, changeset.edit_distance());
+    }
+
+    let changeset = Changeset::with_tokenizer("a\nb\n", "a\nc\n", &Lines);
+    let stats = changeset.stats();
+    assert_eq!((stats.added, stats.removed, stats.unchanged), (1, 1, 1));
+    assert_eq!(stats.added + stats.removed, changeset.edit_distance());
+    assert_eq!(stats.ratio, 0.5);
+}
+
+#[cfg(feature = "unicode-segmentation")]
+#[test]
+fn test_stats_words() {
+    use tokenize::Words;
+
+    let changeset = Changeset::with_tokenizer("hello big world", "hello small world", &Words);
+    let stats = changeset.stats();
+    assert_eq!((stats.added, stats.removed, stats.unchanged), (1, 1, 4));
+    assert_eq!(changeset.edit_distance(), 2);
+    assert_eq!(stats.ratio, 0.8);
+}
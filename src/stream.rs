@@ -0,0 +1,136 @@
+use super::Changeset;
+use std::io;
+use style::Theme;
+
+#[cfg(feature = "termcolor")]
+use super::Difference;
+#[cfg(feature = "termcolor")]
+use style::{Color, Style};
+#[cfg(feature = "termcolor")]
+use termcolor::{self, ColorSpec, WriteColor};
+
+impl Changeset {
+    /// Writes the changeset to `w` the way it is displayed by `Display`.
+    ///
+    /// The output is written incrementally, without building a `String` of the
+    /// whole rendered changeset first, so this is suited for streaming large
+    /// diffs to a file or a pager. Every other renderer of this crate can be
+    /// streamed the same way with `write!(w, "{}", renderer)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::Changeset;
+    /// use std::io;
+    ///
+    /// let changeset = Changeset::new("Diffs are awesome", "Diffs are cool", " ");
+    /// let stdout = io::stdout();
+    /// changeset.write_to(&mut stdout.lock()).unwrap();
+    /// ```
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_styled(w, &Theme::default())
+    }
+
+    /// Writes the changeset to `w`, using the styles of `theme`. See `Changeset::styled`.
+    pub fn write_styled<W: io::Write>(&self, w: &mut W, theme: &Theme) -> io::Result<()> {
+        write!(w, "{}", self.styled(theme))
+    }
+
+    /// Writes the changeset to `w` without colors. See `Changeset::plain`.
+    pub fn write_plain<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "{}", self.plain())
+    }
+
+    /// Writes the changeset to a `termcolor::WriteColor`, using the styles of `theme`.
+    ///
+    /// Unlike `Changeset::write_styled`, this does not write escape codes itself,
+    /// but lets `w` decide how to apply colors, e.g. to use the Windows console
+    /// API or to strip colors when they are disabled.
+    ///
+    /// This requires the `termcolor` feature.
+    #[cfg(feature = "termcolor")]
+    pub fn write_color<W: WriteColor>(&self, w: &mut W, theme: &Theme) -> io::Result<()> {
+        for d in &self.diffs {
+            let (x, style) = match *d {
+                Difference::Same(ref x) => (x, &theme.same),
+                Difference::Add(ref x) => (x, &theme.add),
+                Difference::Rem(ref x) => (x, &theme.rem),
+            };
+            if style.is_plain() {
+                write!(w, "{}", x)?;
+            } else {
+                w.set_color(&color_spec(style))?;
+                write!(w, "{}", x)?;
+                w.reset()?;
+            }
+            write!(w, "{}", self.split)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "termcolor")]
+fn color_spec(style: &Style) -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    spec.set_fg(style.foreground.map(termcolor_color))
+        .set_bg(style.background.map(termcolor_color))
+        .set_bold(style.bold)
+        .set_underline(style.underline);
+    spec
+}
+
+// bright colors are mapped to their 256-color palette entries, because
+// termcolor's `intense` flag would always apply to both fore- and background
+#[cfg(feature = "termcolor")]
+fn termcolor_color(color: Color) -> termcolor::Color {
+    match color {
+        Color::Black => termcolor::Color::Black,
+        Color::Red => termcolor::Color::Red,
+        Color::Green => termcolor::Color::Green,
+        Color::Yellow => termcolor::Color::Yellow,
+        Color::Blue => termcolor::Color::Blue,
+        Color::Magenta => termcolor::Color::Magenta,
+        Color::Cyan => termcolor::Color::Cyan,
+        Color::White => termcolor::Color::White,
+        Color::BrightBlack => termcolor::Color::Ansi256(8),
+        Color::BrightRed => termcolor::Color::Ansi256(9),
+        Color::BrightGreen => termcolor::Color::Ansi256(10),
+        Color::BrightYellow => termcolor::Color::Ansi256(11),
+        Color::BrightBlue => termcolor::Color::Ansi256(12),
+        Color::BrightMagenta => termcolor::Color::Ansi256(13),
+        Color::BrightCyan => termcolor::Color::Ansi256(14),
+        Color::BrightWhite => termcolor::Color::Ansi256(15),
+        Color::Fixed(n) => termcolor::Color::Ansi256(n),
+        Color::Rgb(r, g, b) => termcolor::Color::Rgb(r, g, b),
+    }
+}
+
+#[test]
+fn test_write_to() {
+    let changeset = Changeset::new("a b c", "a x c", " ");
+
+    let mut result: Vec<u8> = Vec::new();
+    changeset.write_to(&mut result).unwrap();
+    assert_eq!(result, changeset.to_string().into_bytes());
+
+    let mut result: Vec<u8> = Vec::new();
+    changeset.write_plain(&mut result).unwrap();
+    assert_eq!(result, b"a [-b-] {+x+} c ".to_vec());
+}
+
+#[cfg(feature = "termcolor")]
+#[test]
+fn test_write_color() {
+    let changeset = Changeset::new("a b c", "a x c", " ");
+
+    let mut w = termcolor::Ansi::new(Vec::new());
+    changeset.write_color(&mut w, &Theme::default()).unwrap();
+    assert_eq!(
+        String::from_utf8(w.into_inner()).unwrap(),
+        "a \x1b[0m\x1b[38;5;9mb\x1b[0m \x1b[0m\x1b[38;5;10mx\x1b[0m c "
+    );
+
+    let mut w = termcolor::NoColor::new(Vec::new());
+    changeset.write_color(&mut w, &Theme::default()).unwrap();
+    assert_eq!(w.into_inner(), b"a b x c ".to_vec());
+}
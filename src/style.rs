@@ -0,0 +1,304 @@
+use std::fmt;
+
+/// A terminal color, used as either foreground or background of a `Style`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Color {
+    /// Black (SGR 30 / 40)
+    Black,
+    /// Red (SGR 31 / 41)
+    Red,
+    /// Green (SGR 32 / 42)
+    Green,
+    /// Yellow (SGR 33 / 43)
+    Yellow,
+    /// Blue (SGR 34 / 44)
+    Blue,
+    /// Magenta (SGR 35 / 45)
+    Magenta,
+    /// Cyan (SGR 36 / 46)
+    Cyan,
+    /// White (SGR 37 / 47)
+    White,
+    /// Bright black, usually rendered as gray (SGR 90 / 100)
+    BrightBlack,
+    /// Bright red (SGR 91 / 101)
+    BrightRed,
+    /// Bright green (SGR 92 / 102)
+    BrightGreen,
+    /// Bright yellow (SGR 93 / 103)
+    BrightYellow,
+    /// Bright blue (SGR 94 / 104)
+    BrightBlue,
+    /// Bright magenta (SGR 95 / 105)
+    BrightMagenta,
+    /// Bright cyan (SGR 96 / 106)
+    BrightCyan,
+    /// Bright white (SGR 97 / 107)
+    BrightWhite,
+    /// A color from the 256-color palette
+    Fixed(u8),
+    /// A 24-bit truecolor value
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    // writes the SGR parameters selecting this color, `base` is 30 for
+    // foreground and 40 for background colors
+    fn write_sgr(&self, f: &mut fmt::Formatter, base: u8) -> fmt::Result {
+        let basic = |n: u8| n + base;
+        let bright = |n: u8| n + base + 60;
+        match *self {
+            Color::Black => write!(f, "{}", basic(0)),
+            Color::Red => write!(f, "{}", basic(1)),
+            Color::Green => write!(f, "{}", basic(2)),
+            Color::Yellow => write!(f, "{}", basic(3)),
+            Color::Blue => write!(f, "{}", basic(4)),
+            Color::Magenta => write!(f, "{}", basic(5)),
+            Color::Cyan => write!(f, "{}", basic(6)),
+            Color::White => write!(f, "{}", basic(7)),
+            Color::BrightBlack => write!(f, "{}", bright(0)),
+            Color::BrightRed => write!(f, "{}", bright(1)),
+            Color::BrightGreen => write!(f, "{}", bright(2)),
+            Color::BrightYellow => write!(f, "{}", bright(3)),
+            Color::BrightBlue => write!(f, "{}", bright(4)),
+            Color::BrightMagenta => write!(f, "{}", bright(5)),
+            Color::BrightCyan => write!(f, "{}", bright(6)),
+            Color::BrightWhite => write!(f, "{}", bright(7)),
+            Color::Fixed(n) => write!(f, "{};5;{}", base + 8, n),
+            Color::Rgb(r, g, b) => write!(f, "{};2;{};{};{}", base + 8, r, g, b),
+        }
+    }
+}
+
+/// The visual appearance of a piece of text in the terminal
+///
+/// The default `Style` is plain text, which is rendered without any escape codes.
+///
+/// # Examples
+///
+/// ```
+/// use difference::{Color, Style};
+///
+/// let style = Style::new().fg(Color::Fixed(208)).underline();
+///
+/// assert_eq!(style.paint("text").to_string(), "\x1b[4;38;5;208mtext\x1b[0m");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Style {
+    /// The foreground (text) color
+    pub foreground: Option<Color>,
+    /// The background color
+    pub background: Option<Color>,
+    /// Whether the text is bold
+    pub bold: bool,
+    /// Whether the text is underlined
+    pub underline: bool,
+}
+
+impl Style {
+    /// Creates a plain `Style` without colors or attributes.
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    /// Returns this style with the given foreground color.
+    pub fn fg(mut self, color: Color) -> Style {
+        self.foreground = Some(color);
+        self
+    }
+
+    /// Returns this style with the given background color.
+    pub fn bg(mut self, color: Color) -> Style {
+        self.background = Some(color);
+        self
+    }
+
+    /// Returns this style in bold.
+    pub fn bold(mut self) -> Style {
+        self.bold = true;
+        self
+    }
+
+    /// Returns this style underlined.
+    pub fn underline(mut self) -> Style {
+        self.underline = true;
+        self
+    }
+
+    /// Whether this style renders as plain text, without any escape codes.
+    pub fn is_plain(&self) -> bool {
+        *self == Style::default()
+    }
+
+    /// Wraps `text` so that it is displayed in this style.
+    pub fn paint<'a>(&'a self, text: &'a str) -> Painted<'a> {
+        Painted {
+            style: self,
+            text,
+        }
+    }
+
+    // writes the escape sequence that switches the terminal to this style
+    pub(crate) fn write_prefix(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_plain() {
+            return Ok(());
+        }
+        write!(f, "\x1b[")?;
+        let mut first = true;
+        let mut sep = |f: &mut fmt::Formatter| if first {
+            first = false;
+            Ok(())
+        } else {
+            write!(f, ";")
+        };
+        if self.bold {
+            sep(f)?;
+            write!(f, "1")?;
+        }
+        if self.underline {
+            sep(f)?;
+            write!(f, "4")?;
+        }
+        if let Some(ref c) = self.foreground {
+            sep(f)?;
+            c.write_sgr(f, 30)?;
+        }
+        if let Some(ref c) = self.background {
+            sep(f)?;
+            c.write_sgr(f, 40)?;
+        }
+        write!(f, "m")
+    }
+
+    // writes the escape sequence that resets the terminal after `write_prefix`
+    pub(crate) fn write_suffix(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_plain() {
+            Ok(())
+        } else {
+            write!(f, "\x1b[0m")
+        }
+    }
+}
+
+/// A piece of text wrapped in a `Style`, created by `Style::paint`
+pub struct Painted<'a> {
+    style: &'a Style,
+    text: &'a str,
+}
+
+impl<'a> fmt::Display for Painted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.style.write_prefix(f)?;
+        write!(f, "{}", self.text)?;
+        self.style.write_suffix(f)
+    }
+}
+
+/// The styles used to render each kind of `Difference`
+///
+/// The default theme prints additions in bright green and removals in bright red,
+/// which is what `Changeset` uses for its `Display` implementation.
+///
+/// # Examples
+///
+/// ```
+/// use difference::{Changeset, Color, Style, Theme};
+///
+/// let theme = Theme {
+///     add: Style::new().fg(Color::Blue).bold(),
+///     ..Theme::default()
+/// };
+///
+/// let changeset = Changeset::new("Diffs are awesome", "Diffs are cool", " ");
+/// println!("{}", changeset.styled(&theme));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Theme {
+    /// The style of `Difference::Same`
+    pub same: Style,
+    /// The style of `Difference::Add`
+    pub add: Style,
+    /// The style of `Difference::Rem`
+    pub rem: Style,
+    /// The style of the changed parts of an addition, see `Changeset::refine`
+    pub add_emphasis: Style,
+    /// The style of the changed parts of a removal, see `Changeset::refine`
+    pub rem_emphasis: Style,
+    /// The style of an addition that was moved from elsewhere, see `Styled::color_moved`
+    pub moved_add: Style,
+    /// The style of a removal that was moved elsewhere, see `Styled::color_moved`
+    pub moved_rem: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            same: Style::new(),
+            add: Style::new().fg(Color::BrightGreen),
+            rem: Style::new().fg(Color::BrightRed),
+            add_emphasis: Style::new().fg(Color::BrightWhite).bg(Color::Green),
+            rem_emphasis: Style::new().fg(Color::BrightWhite).bg(Color::Red),
+            moved_add: Style::new().fg(Color::BrightCyan).bold(),
+            moved_rem: Style::new().fg(Color::BrightMagenta).bold(),
+        }
+    }
+}
+
+impl Theme {
+    /// A theme that avoids telling changes apart by red and green alone:
+    /// additions are blue, removals are orange and underlined.
+    pub fn colorblind() -> Theme {
+        Theme {
+            same: Style::new(),
+            add: Style::new().fg(Color::Fixed(33)),
+            rem: Style::new().fg(Color::Fixed(208)).underline(),
+            add_emphasis: Style::new().fg(Color::BrightWhite).bg(Color::Fixed(33)),
+            rem_emphasis: Style::new().fg(Color::Black).bg(Color::Fixed(208)),
+            moved_add: Style::new().fg(Color::Fixed(33)).bold(),
+            moved_rem: Style::new().fg(Color::Fixed(208)).bold().underline(),
+        }
+    }
+
+    /// A theme for light terminal backgrounds, using dark colors for the text.
+    pub fn light() -> Theme {
+        Theme {
+            same: Style::new(),
+            add: Style::new().fg(Color::Green),
+            rem: Style::new().fg(Color::Red),
+            add_emphasis: Style::new().fg(Color::Black).bg(Color::BrightGreen),
+            rem_emphasis: Style::new().fg(Color::Black).bg(Color::BrightRed),
+            moved_add: Style::new().fg(Color::Cyan).bold(),
+            moved_rem: Style::new().fg(Color::Magenta).bold(),
+        }
+    }
+}
+
+#[test]
+fn test_paint() {
+    assert_eq!(Style::new().paint("a").to_string(), "a");
+    assert_eq!(
+        Style::new().fg(Color::BrightRed).paint("a").to_string(),
+        "\x1b[91ma\x1b[0m"
+    );
+    assert_eq!(
+        Style::new()
+            .fg(Color::White)
+            .bg(Color::Green)
+            .bold()
+            .paint("a")
+            .to_string(),
+        "\x1b[1;37;42ma\x1b[0m"
+    );
+    assert_eq!(
+        Style::new()
+            .fg(Color::Rgb(1, 2, 3))
+            .bg(Color::Fixed(4))
+            .paint("a")
+            .to_string(),
+        "\x1b[38;2;1;2;3;48;5;4ma\x1b[0m"
+    );
+    assert_eq!(
+        Style::new().bg(Color::BrightBlack).paint("a").to_string(),
+        "\x1b[100ma\x1b[0m"
+    );
+}
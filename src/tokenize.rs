@@ -0,0 +1,302 @@
+use super::Changeset;
+use lcs::lcs;
+use merge::merge;
+
+#[cfg(test)]
+use super::Difference;
+#[cfg(feature = "regex")]
+use regex::Regex;
+#[cfg(feature = "unicode-segmentation")]
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Splits text into the tokens that are compared when diffing, see
+/// `Changeset::with_tokenizer`
+///
+/// The returned tokens should cover the whole text, including any delimiters,
+/// so that concatenating them gives back the text.
+pub trait Tokenizer {
+    /// Splits `text` into tokens
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str>;
+
+    /// Whether every token is a whole line including its terminator. The
+    /// changeset is then rendered line by line, like one split by `"\n"`.
+    fn line_tokens(&self) -> bool {
+        false
+    }
+}
+
+/// Splits text into extended grapheme clusters, i.e. user-perceived characters
+///
+/// Unlike splitting by `""`, this keeps emoji, flags and combining sequences
+/// together. This requires the `unicode-segmentation` feature.
+#[cfg(feature = "unicode-segmentation")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Graphemes;
+
+#[cfg(feature = "unicode-segmentation")]
+impl Tokenizer for Graphemes {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        text.graphemes(true).collect()
+    }
+}
+
+/// Splits text at word boundaries as defined by
+/// [UAX #29](https://unicode.org/reports/tr29/#Word_Boundaries)
+///
+/// Whitespace and punctuation between words are tokens of their own.
+/// This requires the `unicode-segmentation` feature.
+#[cfg(feature = "unicode-segmentation")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Words;
+
+#[cfg(feature = "unicode-segmentation")]
+impl Tokenizer for Words {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        text.split_word_bounds().collect()
+    }
+}
+
+/// Splits text into lines, each including its `"\n"` or `"\r\n"` terminator
+///
+/// Unlike splitting by `"\n"`, a missing newline at the end of the text shows
+/// up as a change of the last line. Changesets of lines are still rendered
+/// line by line by `Changeset::plain`, `Changeset::markdown` and
+/// `Changeset::numbered`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Lines;
+
+impl Tokenizer for Lines {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        text.split_inclusive('\n').collect()
+    }
+
+    fn line_tokens(&self) -> bool {
+        true
+    }
+}
+
+/// Splits text at the matches of the regex, keeping the matches as tokens of
+/// their own. See `Changeset::split_regex`.
+#[cfg(feature = "regex")]
+impl Tokenizer for Regex {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut tokens = Vec::new();
+        let mut last = 0;
+        for m in self.find_iter(text) {
+            tokens.push(&text[last..m.start()]);
+            tokens.push(m.as_str());
+            last = m.end();
+        }
+        tokens.push(&text[last..]);
+        tokens.retain(|x| !x.is_empty());
+        tokens
+    }
+}
+
+impl Changeset {
+    /// Calculates the changeset for two given strings, split into tokens by
+    /// `tokenize` instead of a separator string.
+    ///
+    /// The tokens returned by `tokenize` should cover the whole text, including
+    /// any delimiters, so that concatenating them gives back the text. The
+    /// changeset then has an empty `split` and is displayed faithfully.
+    ///
+    /// # Examples
+    ///
+    /// Split text into runs of word and non-word characters:
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference};
+    ///
+    /// let changeset = Changeset::tokenized("foo, bar", "foo bar", |text| {
+    ///     let mut tokens = Vec::new();
+    ///     let mut start = 0;
+    ///     for (i, c) in text.char_indices().skip(1) {
+    ///         let prev = text[..i].chars().next_back().unwrap();
+    ///         if prev.is_alphanumeric() != c.is_alphanumeric() {
+    ///             tokens.push(&text[start..i]);
+    ///             start = i;
+    ///         }
+    ///     }
+    ///     tokens.push(&text[start..]);
+    ///     tokens
+    /// });
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("foo".to_string()),
+    ///     Difference::Rem(", ".to_string()),
+    ///     Difference::Add(" ".to_string()),
+    ///     Difference::Same("bar".to_string()),
+    /// ]);
+    /// ```
+    pub fn tokenized<'a, F>(orig: &'a str, edit: &'a str, tokenize: F) -> Changeset
+    where
+        F: Fn(&'a str) -> Vec<&'a str>,
+    {
+        Changeset::from_tokens(&tokenize(orig), &tokenize(edit))
+    }
+
+    // calculates the changeset of two token lists that cover the whole texts,
+    // skipping empty tokens
+    fn from_tokens(a: &[&str], b: &[&str]) -> Changeset {
+        let a: Vec<&str> = a.iter().cloned().filter(|x| !x.is_empty()).collect();
+        let b: Vec<&str> = b.iter().cloned().filter(|x| !x.is_empty()).collect();
+
+        Changeset::from_kinds(&merge(&a, &b, &lcs(&a, &b), |_, _, _, _| false), "", true)
+    }
+
+    /// Calculates the changeset for two given strings, split at the matches of
+    /// `delimiter`. Unlike `Changeset::new`, the delimiters are kept as tokens of
+    /// their own, so `"foo,"` and `"foo"` only differ in the `","`.
+    ///
+    /// This requires the `regex` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate regex;
+    /// extern crate difference;
+    /// use difference::{Changeset, Difference};
+    /// use regex::Regex;
+    ///
+    /// # fn main() {
+    /// let words = Regex::new(r"[\s[:punct:]]+").unwrap();
+    /// let changeset = Changeset::split_regex("Hello world,\nbye", "Hello world\nbye!", &words);
+    ///
+    /// assert_eq!(changeset.distance, 3);
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("Hello world".to_string()),
+    ///     Difference::Rem(",\n".to_string()),
+    ///     Difference::Add("\n".to_string()),
+    ///     Difference::Same("bye".to_string()),
+    ///     Difference::Add("!".to_string()),
+    /// ]);
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn split_regex(orig: &str, edit: &str, delimiter: &Regex) -> Changeset {
+        Changeset::with_tokenizer(orig, edit, delimiter)
+    }
+
+    /// Calculates the changeset for two given strings, split into tokens by
+    /// `tokenizer`. See `Changeset::tokenized`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, Difference, Lines};
+    ///
+    /// let changeset = Changeset::with_tokenizer("a\nb\n", "a\nb", &Lines);
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("a\n".to_string()),
+    ///     Difference::Rem("b\n".to_string()),
+    ///     Difference::Add("b".to_string()),
+    /// ]);
+    /// ```
+    pub fn with_tokenizer<T: Tokenizer + ?Sized>(orig: &str, edit: &str, tokenizer: &T) -> Changeset {
+        let a: Vec<&str> = tokenizer.tokenize(orig);
+        let b: Vec<&str> = tokenizer.tokenize(edit);
+        let mut changeset = Changeset::from_tokens(&a, &b);
+        changeset.lines = tokenizer.line_tokens();
+        changeset
+    }
+}
+
+#[test]
+fn test_tokenized() {
+    let changeset = Changeset::tokenized("a\nb\nc", "a\nc\n", |text| text.split_inclusive('\n').collect());
+    assert_eq!(changeset.distance, 3);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("a\n".to_string()),
+            Difference::Rem("b\nc".to_string()),
+            Difference::Add("c\n".to_string()),
+        ]
+    );
+    assert_eq!(changeset.split, "");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_split_regex() {
+    let spaces = Regex::new(r"\s+").unwrap();
+    assert_eq!(spaces.tokenize(" a  b\nc"), vec![" ", "a", "  ", "b", "\n", "c"]);
+
+    let changeset = Changeset::split_regex("a b\nc", "a b c", &spaces);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("a b".to_string()),
+            Difference::Rem("\n".to_string()),
+            Difference::Add(" ".to_string()),
+            Difference::Same("c".to_string()),
+        ]
+    );
+}
+
+#[cfg(feature = "unicode-segmentation")]
+#[test]
+fn test_unicode_tokenizers() {
+    // a family emoji joined by zero width joiners, and "é" with a combining accent
+    let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+    assert_eq!(Graphemes.tokenize("e\u{301}x"), vec!["e\u{301}", "x"]);
+
+    let changeset = Changeset::with_tokenizer(&format!("a{}b", family), "a\u{1f468}b", &Graphemes);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Same("a".to_string()),
+            Difference::Rem(family.to_string()),
+            Difference::Add("\u{1f468}".to_string()),
+            Difference::Same("b".to_string()),
+        ]
+    );
+
+    assert_eq!(
+        Words.tokenize("can't stop, won't"),
+        vec!["can't", " ", "stop", ",", " ", "won't"]
+    );
+}
+
+#[test]
+fn test_lines() {
+    assert_eq!(Lines.tokenize("a\r\nb\n\nc"), vec!["a\r\n", "b\n", "\n", "c"]);
+    assert_eq!(Lines.tokenize(""), Vec::<&str>::new());
+}
+
+#[test]
+fn test_render_lines() {
+    use style::{Style, Theme};
+
+    let changeset = Changeset::with_tokenizer("a\nb\n", "a\nc\nb", &Lines);
+    assert_eq!(changeset.plain().to_string(), " a\n-b\n+c\n+b\n\\ No newline at end of file\n");
+    assert_eq!(
+        changeset.markdown().to_string(),
+        "```diff\n a\n-b\n+c\n+b\n\\ No newline at end of file\n```\n"
+    );
+
+    let theme = Theme {
+        add: Style::new(),
+        rem: Style::new(),
+        ..Theme::default()
+    };
+    let expected = [
+        "  1  1 │  a",
+        "  2    │ -b",
+        "     2 │ +c",
+        "     3 │ +b",
+        "",
+    ];
+    assert_eq!(changeset.numbered(&theme).to_string(), expected.join("\n"));
+}
+
+#[test]
+fn test_render_lines_carriage_return() {
+    let changeset = Changeset::with_tokenizer("a\rb\n", "a\rc\n", &Lines);
+    assert_eq!(changeset.plain().to_string(), "-a\rb\n+a\rc\n");
+
+    let mut changeset = Changeset::with_tokenizer("a\nb\n", "a\nc\n", &Lines);
+    changeset.diffs.push(Difference::Same("d\n".to_string()));
+    assert_eq!(changeset.plain().to_string(), " a\n-b\n+c\n d\n");
+}
@@ -0,0 +1,382 @@
+use super::{Changeset, Difference};
+use lcs::Op;
+use std::fmt;
+use std::ops::Range;
+use xdiff::{diff, is_space};
+
+/// The output formats of `git diff --word-diff`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WordDiffMode {
+    /// `--word-diff=plain`, marking removals as `[-...-]` and additions as `{+...+}`
+    Plain,
+    /// `--word-diff=porcelain`, a line-based format for scripts, where every line
+    /// starts with ` `, `-` or `+` and line breaks of the input are marked by `~`
+    Porcelain,
+    /// `--word-diff=color`, showing removals in red and additions in green
+    Color,
+}
+
+/// A `Changeset` rendered like `git diff --word-diff`, created by
+/// `Changeset::word_diff`
+pub struct WordDiff<'a> {
+    changeset: &'a Changeset,
+    mode: WordDiffMode,
+}
+
+impl Changeset {
+    /// Renders the changeset in one of the formats of `git diff --word-diff`.
+    ///
+    /// Like git, this compares the texts line by line, and then the words of
+    /// changed lines, where words are runs of characters other than spaces,
+    /// tabs and line breaks. The output is the same as that of git for the
+    /// original and edited texts, whatever `split` the changeset was created
+    /// with. Only the body of the diff is rendered, without file or hunk
+    /// headers, and every line ends with a line break.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference::{Changeset, WordDiffMode};
+    ///
+    /// let changeset = Changeset::new("one two\nthree", "one two\nfour", " ");
+    ///
+    /// assert_eq!(
+    ///     changeset.word_diff(WordDiffMode::Plain).to_string(),
+    ///     "one two\n[-three-]{+four+}\n"
+    /// );
+    /// assert_eq!(
+    ///     changeset.word_diff(WordDiffMode::Porcelain).to_string(),
+    ///     " one two\n~\n-three\n+four\n~\n"
+    /// );
+    /// ```
+    pub fn word_diff<'a>(&'a self, mode: WordDiffMode) -> WordDiff<'a> {
+        WordDiff {
+            changeset: self,
+            mode,
+        }
+    }
+}
+
+// how git writes unchanged, removed or added text: every part of it between
+// line breaks is put between a prefix and a suffix
+type Elem = (&'static str, &'static str);
+
+impl<'a> WordDiff<'a> {
+    // the original and the edited text
+    fn texts(&self) -> (String, String) {
+        let mut orig: Vec<&str> = Vec::new();
+        let mut edit: Vec<&str> = Vec::new();
+        for d in &self.changeset.diffs {
+            match *d {
+                Difference::Same(ref x) => {
+                    orig.push(x);
+                    edit.push(x);
+                }
+                Difference::Rem(ref x) => orig.push(x),
+                Difference::Add(ref x) => edit.push(x),
+            }
+        }
+        let split = &self.changeset.split;
+        (orig.join(split), edit.join(split))
+    }
+
+    // the styles of unchanged, removed and added text, and what is written for
+    // a line break
+    fn styles(&self) -> (Elem, Elem, Elem, &'static str) {
+        match self.mode {
+            WordDiffMode::Plain => (("", ""), ("[-", "-]"), ("{+", "+}"), "\n"),
+            WordDiffMode::Porcelain => ((" ", "\n"), ("-", "\n"), ("+", "\n"), "~\n"),
+            WordDiffMode::Color => (("", ""), ("\x1b[31m", "\x1b[m"), ("\x1b[32m", "\x1b[m"), "\n"),
+        }
+    }
+
+    // writes the removed and added lines between two unchanged lines, like
+    // `diff_words_show` of git
+    //
+    // The changes are found between the words of both texts, and written
+    // together with the text of `plus` around them.
+    fn write_words(&self, f: &mut fmt::Formatter, minus: &str, plus: &str) -> fmt::Result {
+        let (ctx, old, new, newline) = self.styles();
+        if plus.is_empty() {
+            return write_text(f, minus, old, newline);
+        }
+
+        let mw = words(minus);
+        let pw = words(plus);
+        let mt: Vec<&str> = mw.iter().map(|r| &minus[r.clone()]).collect();
+        let pt: Vec<&str> = pw.iter().map(|r| &plus[r.clone()]).collect();
+        // an empty range of words is at the end of the word before it
+        let at = |w: &[Range<usize>], i: usize, j: usize| {
+            if i < j {
+                (w[i].start, w[j - 1].end)
+            } else {
+                let end = if i > 0 { w[i - 1].end } else { 0 };
+                (end, end)
+            }
+        };
+
+        // like git, the words of a long common end are left out of the diff
+        let tail = common_tail(&mt, &pt);
+        let mut ops = diff(&mt[..mt.len() - tail], &pt[..pt.len() - tail], false);
+        ops.extend((0..tail).map(|k| Op::Same(mt.len() - tail + k, pt.len() - tail + k)));
+        let (mut i, mut j) = (0, 0);
+        let mut current = 0;
+        let mut k = 0;
+        while k < ops.len() {
+            if let Op::Same(..) = ops[k] {
+                i += 1;
+                j += 1;
+                k += 1;
+                continue;
+            }
+            let (i0, j0) = (i, j);
+            while k < ops.len() {
+                match ops[k] {
+                    Op::Rem(_) => i += 1,
+                    Op::Add(_) => j += 1,
+                    Op::Same(..) => break,
+                }
+                k += 1;
+            }
+
+            let (minus_begin, minus_end) = at(&mw, i0, i);
+            let (plus_begin, plus_end) = at(&pw, j0, j);
+            write_text(f, &plus[current..plus_begin], ctx, newline)?;
+            write_text(f, &minus[minus_begin..minus_end], old, newline)?;
+            write_text(f, &plus[plus_begin..plus_end], new, newline)?;
+            current = plus_end;
+        }
+        write_text(f, &plus[current..], ctx, newline)
+    }
+}
+
+// the byte ranges of the words of `x`, i.e. the runs of characters that are
+// not whitespace as git defines it
+fn words(x: &str) -> Vec<Range<usize>> {
+    let bytes = x.as_bytes();
+    let mut ret = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if is_space(bytes[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && !is_space(bytes[i]) {
+            i += 1;
+        }
+        ret.push(start..i);
+    }
+    ret
+}
+
+// the number of words at the end of both texts that git leaves out of the
+// diff, like `trim_common_tail`
+//
+// Git diffs the words as lines, and drops their common end in blocks of 1024
+// bytes, keeping the part of the last block before its first line break.
+fn common_tail(a: &[&str], b: &[&str]) -> usize {
+    const BLOCK: usize = 1024;
+    let lines = |x: &[&str]| x.iter().flat_map(|w| w.bytes().chain(Some(b'\n'))).collect::<Vec<u8>>();
+    let (a, b) = (lines(a), lines(b));
+    let smaller = a.len().min(b.len());
+    let mut trimmed = 0;
+    while trimmed + BLOCK <= smaller
+        && a[a.len() - trimmed - BLOCK..a.len() - trimmed] == b[b.len() - trimmed - BLOCK..b.len() - trimmed]
+    {
+        trimmed += BLOCK;
+    }
+    let tail = &a[a.len() - trimmed..];
+    let recovered = tail.iter().position(|&c| c == b'\n').map_or(trimmed, |i| i + 1);
+    tail[recovered..].iter().filter(|&&c| c == b'\n').count()
+}
+
+// writes text in a style, putting every part between line breaks between the
+// prefix and suffix of the style and writing `newline` for every line break,
+// like `fn_out_diff_words_write_helper` of git
+fn write_text(f: &mut fmt::Formatter, x: &str, (prefix, suffix): Elem, newline: &str) -> fmt::Result {
+    let mut rest = x;
+    while !rest.is_empty() {
+        let (part, next) = match rest.find('\n') {
+            Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+            None => (rest, None),
+        };
+        if !part.is_empty() {
+            write!(f, "{}{}{}", prefix, part, suffix)?;
+        }
+        match next {
+            Some(next) => {
+                f.write_str(newline)?;
+                rest = next;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+// appends a line to a text, with a line break at its end like git writes it
+fn push_line(text: &mut String, line: &str) {
+    text.push_str(line);
+    if !line.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+impl<'a> fmt::Display for WordDiff<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (orig, edit) = self.texts();
+        let a: Vec<&str> = orig.split_inclusive('\n').collect();
+        let b: Vec<&str> = edit.split_inclusive('\n').collect();
+
+        let mut minus = String::new();
+        let mut plus = String::new();
+        for op in diff(&a, &b, true) {
+            match op {
+                Op::Same(i, _) => {
+                    self.write_words(f, &minus, &plus)?;
+                    minus.clear();
+                    plus.clear();
+
+                    let line = a[i].strip_suffix('\n').unwrap_or(a[i]);
+                    match self.mode {
+                        WordDiffMode::Plain => writeln!(f, "{}", line)?,
+                        WordDiffMode::Porcelain => write!(f, " {}\n~\n", line)?,
+                        // git resets the color after unchanged lines that are not empty
+                        WordDiffMode::Color if line.is_empty() => f.write_str("\n")?,
+                        WordDiffMode::Color => writeln!(f, "{}\x1b[m", line)?,
+                    }
+                }
+                Op::Rem(i) => push_line(&mut minus, a[i]),
+                Op::Add(j) => push_line(&mut plus, b[j]),
+            }
+        }
+        self.write_words(f, &minus, &plus)
+    }
+}
+
+#[test]
+fn test_word_diff() {
+    let text1 = "Roses are red, violets are blue,\n\
+                 I wrote this library for you.";
+
+    let text2 = "Roses are red, violets are blue,\n\
+                 I wrote this documentation just for you.";
+
+    let changeset = Changeset::new(text1, text2, " ");
+
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Plain).to_string(),
+        "Roses are red, violets are blue,\n\
+         I wrote this [-library-]{+documentation just+} for you.\n"
+    );
+
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Porcelain).to_string(),
+        " Roses are red, violets are blue,\n\
+         ~\n\
+         \x20I wrote this \n\
+         -library\n\
+         +documentation just\n\
+         \x20 for you.\n\
+         ~\n"
+    );
+
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Color).to_string(),
+        "Roses are red, violets are blue,\x1b[m\n\
+         I wrote this \x1b[31mlibrary\x1b[m\x1b[32mdocumentation just\x1b[m for you.\n"
+    );
+
+    let changeset = Changeset::new(
+        "Roses are red,\nviolets are blue,\nI wrote this library.",
+        "Roses are red,\nviolets are blue,\nI wrote this documentation.",
+        " ",
+    );
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Porcelain).to_string(),
+        " Roses are red,\n\
+         ~\n\
+         \x20violets are blue,\n\
+         ~\n\
+         \x20I wrote this \n\
+         -library.\n\
+         +documentation.\n\
+         ~\n"
+    );
+}
+
+#[test]
+fn test_word_diff_insert_delete() {
+    // removed and added words are written at the end of the word before them
+    let changeset = Changeset::new("a b c", "a c d", " ");
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Plain).to_string(),
+        "a[-b-] c {+d+}\n"
+    );
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Porcelain).to_string(),
+        " a\n-b\n  c \n+d\n~\n"
+    );
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Color).to_string(),
+        "a\x1b[31mb\x1b[m c \x1b[32md\x1b[m\n"
+    );
+
+    let changeset = Changeset::new("", "a b", " ");
+    assert_eq!(changeset.word_diff(WordDiffMode::Plain).to_string(), "{+a b+}\n");
+    assert_eq!(changeset.word_diff(WordDiffMode::Porcelain).to_string(), "+a b\n~\n");
+
+    let changeset = Changeset::new("a b", "", " ");
+    assert_eq!(changeset.word_diff(WordDiffMode::Plain).to_string(), "[-a b-]\n");
+    assert_eq!(changeset.word_diff(WordDiffMode::Porcelain).to_string(), "-a b\n~\n");
+}
+
+#[test]
+fn test_word_diff_line_breaks() {
+    // only the changed lines are compared word by word
+    let changeset = Changeset::new("x y\nz", "x y\nw", " ");
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Plain).to_string(),
+        "x y\n[-z-]{+w+}\n"
+    );
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Porcelain).to_string(),
+        " x y\n~\n-z\n+w\n~\n"
+    );
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Color).to_string(),
+        "x y\x1b[m\n\x1b[31mz\x1b[m\x1b[32mw\x1b[m\n"
+    );
+
+    let changeset = Changeset::new("a\nb\nc", "a\nc", "\n");
+    assert_eq!(changeset.word_diff(WordDiffMode::Plain).to_string(), "a\n[-b-]\nc\n");
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Porcelain).to_string(),
+        " a\n~\n-b\n~\n c\n~\n"
+    );
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Color).to_string(),
+        "a\x1b[m\n\x1b[31mb\x1b[m\nc\x1b[m\n"
+    );
+
+    // a missing line break at the end changes the last line, but not its words
+    let changeset = Changeset::new("a\nb", "a\nb\n", "\n");
+    assert_eq!(changeset.word_diff(WordDiffMode::Plain).to_string(), "a\nb\n");
+}
+
+#[test]
+fn test_word_diff_alignment() {
+    // the words and lines are aligned like git aligns them
+    let changeset = Changeset::new("b a", "a b aaa", " ");
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Plain).to_string(),
+        "[-b-]a {+b aaa+}\n"
+    );
+
+    let changeset = Changeset::new("{\n  x\n}\n", "{\n  x\n}\n{\n  y\n}\n", "\n");
+    assert_eq!(
+        changeset.word_diff(WordDiffMode::Plain).to_string(),
+        "{\n  x\n}\n{+{+}\n{+  y+}\n{+}+}\n"
+    );
+}
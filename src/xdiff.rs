@@ -0,0 +1,845 @@
+use lcs::Op;
+use std::collections::HashMap;
+
+// A port of the diff algorithm of git's xdiff library, for output that has to
+// match that of git exactly, see `WordDiff`. The alignment of `lcs` is often
+// different when there are several longest common subsequences.
+//
+// Like xdiff, this trims the common start and end of both sequences, discards
+// tokens that have no match or too many matches in the other sequence, aligns
+// the rest with the divide and conquer algorithm of Myers, and finally slides
+// groups of changes down as far as possible.
+
+// the limit of the number of occurrences of a token, above which it may be
+// discarded before aligning
+const MAX_EQLIMIT: usize = 1024;
+// the range of tokens around a token with many matches considered by `mmatch`
+const SIMSCAN_WINDOW: usize = 100;
+const KPDIS_RUN: usize = 4;
+const MAX_COST_MIN: isize = 256;
+const HEUR_MIN_COST: isize = 256;
+const SNAKE_CNT: isize = 20;
+const K_HEUR: isize = 4;
+
+// a sequence of tokens, and which of them are changed
+struct File<'a, 'b> {
+    recs: &'b [&'a str],
+    // the equivalence class of every token
+    ha: Vec<usize>,
+    // whether every token is changed, with an unchanged token before and
+    // after the sequence
+    rchg: Vec<bool>,
+    // the tokens that are aligned by `split`, and their classes
+    rindex: Vec<usize>,
+    reff: Vec<usize>,
+    dstart: usize,
+    dend: isize,
+}
+
+impl<'a, 'b> File<'a, 'b> {
+    fn changed(&self, i: isize) -> bool {
+        self.rchg[(i + 1) as usize]
+    }
+
+    fn set_changed(&mut self, i: isize, changed: bool) {
+        self.rchg[(i + 1) as usize] = changed;
+    }
+
+    fn len(&self) -> isize {
+        self.recs.len() as isize
+    }
+}
+
+// aligns `a` and `b` like git, with the indent heuristic of `git diff` if
+// `indent_heuristic` is true
+pub fn diff<'a>(a: &[&'a str], b: &[&'a str], indent_heuristic: bool) -> Vec<Op> {
+    let mut classes: HashMap<&'a str, usize> = HashMap::new();
+    // the number of occurrences of every class in `a` and `b`
+    let mut counts: Vec<(usize, usize)> = Vec::new();
+    let mut classify = |recs: &[&'a str], second: bool| -> Vec<usize> {
+        let mut ret = Vec::with_capacity(recs.len());
+        for &x in recs {
+            let next = classes.len();
+            let class = *classes.entry(x).or_insert(next);
+            if class == counts.len() {
+                counts.push((0, 0));
+            }
+            if second {
+                counts[class].1 += 1;
+            } else {
+                counts[class].0 += 1;
+            }
+            ret.push(class);
+        }
+        ret
+    };
+    let ha1 = classify(a, false);
+    let ha2 = classify(b, true);
+
+    let mut f1 = File {
+        recs: a,
+        ha: ha1,
+        rchg: vec![false; a.len() + 2],
+        rindex: Vec::new(),
+        reff: Vec::new(),
+        dstart: 0,
+        dend: 0,
+    };
+    let mut f2 = File {
+        recs: b,
+        ha: ha2,
+        rchg: vec![false; b.len() + 2],
+        rindex: Vec::new(),
+        reff: Vec::new(),
+        dstart: 0,
+        dend: 0,
+    };
+
+    trim_ends(&mut f1, &mut f2);
+    cleanup_records(&mut f1, &counts, |c| c.1);
+    cleanup_records(&mut f2, &counts, |c| c.0);
+    recs_cmp(&mut f1, &mut f2);
+    change_compact(&mut f1, &f2, indent_heuristic);
+    change_compact(&mut f2, &f1, indent_heuristic);
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && f1.changed(i as isize) {
+            ops.push(Op::Rem(i));
+            i += 1;
+        } else if j < b.len() && f2.changed(j as isize) {
+            ops.push(Op::Add(j));
+            j += 1;
+        } else {
+            ops.push(Op::Same(i, j));
+            i += 1;
+            j += 1;
+        }
+    }
+    ops
+}
+
+// an approximation of the square root of `n` by shifts, like `xdl_bogosqrt`
+fn bogosqrt(mut n: usize) -> usize {
+    let mut ret = 1;
+    while n > 0 {
+        ret <<= 1;
+        n >>= 2;
+    }
+    ret
+}
+
+// excludes the common start and end of both sequences from the alignment
+fn trim_ends(f1: &mut File, f2: &mut File) {
+    let lim = f1.recs.len().min(f2.recs.len());
+    let mut i = 0;
+    while i < lim && f1.ha[i] == f2.ha[i] {
+        i += 1;
+    }
+    f1.dstart = i;
+    f2.dstart = i;
+
+    let (n1, n2) = (f1.recs.len(), f2.recs.len());
+    let lim = lim - i;
+    let mut i = 0;
+    while i < lim && f1.ha[n1 - 1 - i] == f2.ha[n2 - 1 - i] {
+        i += 1;
+    }
+    f1.dend = n1 as isize - i as isize - 1;
+    f2.dend = n2 as isize - i as isize - 1;
+}
+
+// marks the tokens without a match in the other sequence as changed, as well
+// as tokens with many matches in runs of such tokens, and collects the rest
+// for the alignment
+//
+// `other` gives the number of occurrences of a class in the other sequence.
+fn cleanup_records<F>(f: &mut File, counts: &[(usize, usize)], other: F)
+where
+    F: Fn(&(usize, usize)) -> usize,
+{
+    let mlim = bogosqrt(f.recs.len()).min(MAX_EQLIMIT);
+    let start = f.dstart as isize;
+    // 0 for tokens without a match, 2 for tokens with many matches, 1 otherwise
+    let mut dis = vec![0u8; f.recs.len()];
+    let mut i = start;
+    while i <= f.dend {
+        let nm = other(&counts[f.ha[i as usize]]);
+        dis[i as usize] = if nm == 0 {
+            0
+        } else if nm >= mlim {
+            2
+        } else {
+            1
+        };
+        i += 1;
+    }
+
+    let mut i = start;
+    while i <= f.dend {
+        let k = i as usize;
+        if dis[k] == 1 || (dis[k] == 2 && !mmatch(&dis, k, f.dstart, f.dend as usize)) {
+            f.rindex.push(k);
+            f.reff.push(f.ha[k]);
+        } else {
+            f.set_changed(i, true);
+        }
+        i += 1;
+    }
+}
+
+// whether the token `i` with many matches is surrounded by a run of tokens
+// without or with many matches, which is mostly made of the former, like
+// `xdl_clean_mmatch`
+fn mmatch(dis: &[u8], i: usize, s: usize, e: usize) -> bool {
+    let s = if i - s > SIMSCAN_WINDOW { i - SIMSCAN_WINDOW } else { s };
+    let e = if e - i > SIMSCAN_WINDOW { i + SIMSCAN_WINDOW } else { e };
+
+    let (mut rdis0, mut rpdis0) = (0, 1);
+    let mut r = 1;
+    while i >= s + r {
+        match dis[i - r] {
+            0 => rdis0 += 1,
+            2 => rpdis0 += 1,
+            _ => break,
+        }
+        r += 1;
+    }
+    if rdis0 == 0 {
+        return false;
+    }
+    let (mut rdis1, mut rpdis1) = (0, 1);
+    let mut r = 1;
+    while i + r <= e {
+        match dis[i + r] {
+            0 => rdis1 += 1,
+            2 => rpdis1 += 1,
+            _ => break,
+        }
+        r += 1;
+    }
+    if rdis1 == 0 {
+        return false;
+    }
+    rdis1 += rdis0;
+    rpdis1 += rpdis0;
+    rpdis1 * KPDIS_RUN < rpdis1 + rdis1
+}
+
+// where `split` divides a box of the alignment, and whether both halves
+// need a minimal alignment
+struct Split {
+    i1: isize,
+    i2: isize,
+    min_lo: bool,
+    min_hi: bool,
+}
+
+// the furthest reaching paths on the diagonals, indexed by diagonal with
+// an offset, since diagonals may be negative
+struct Diagonals {
+    v: Vec<isize>,
+    offset: isize,
+}
+
+impl Diagonals {
+    fn get(&self, d: isize) -> isize {
+        self.v[(d + self.offset) as usize]
+    }
+
+    fn set(&mut self, d: isize, x: isize) {
+        self.v[(d + self.offset) as usize] = x;
+    }
+}
+
+// aligns the collected tokens of both sequences, marking the ones that are
+// not part of the alignment as changed, like `xdl_recs_cmp`
+fn recs_cmp(f1: &mut File, f2: &mut File) {
+    let (n1, n2) = (f1.reff.len() as isize, f2.reff.len() as isize);
+    let ndiags = (n1 + n2 + 3) as usize;
+    let mut kvdf = Diagonals {
+        v: vec![0; ndiags],
+        offset: n2 + 1,
+    };
+    let mut kvdb = Diagonals {
+        v: vec![0; ndiags],
+        offset: n2 + 1,
+    };
+    let mxcost = (bogosqrt(ndiags) as isize).max(MAX_COST_MIN);
+
+    let mut boxes = vec![(0, n1, 0, n2, false)];
+    while let Some((mut off1, mut lim1, mut off2, mut lim2, need_min)) = boxes.pop() {
+        let (ha1, ha2) = (&f1.reff, &f2.reff);
+        while off1 < lim1 && off2 < lim2 && ha1[off1 as usize] == ha2[off2 as usize] {
+            off1 += 1;
+            off2 += 1;
+        }
+        while off1 < lim1 && off2 < lim2 && ha1[lim1 as usize - 1] == ha2[lim2 as usize - 1] {
+            lim1 -= 1;
+            lim2 -= 1;
+        }
+
+        if off1 == lim1 {
+            for k in off2..lim2 {
+                let i = f2.rindex[k as usize] as isize;
+                f2.set_changed(i, true);
+            }
+        } else if off2 == lim2 {
+            for k in off1..lim1 {
+                let i = f1.rindex[k as usize] as isize;
+                f1.set_changed(i, true);
+            }
+        } else {
+            let spl = split(ha1, off1, lim1, ha2, off2, lim2, &mut kvdf, &mut kvdb, need_min, mxcost);
+            boxes.push((spl.i1, lim1, spl.i2, lim2, spl.min_hi));
+            boxes.push((off1, spl.i1, off2, spl.i2, spl.min_lo));
+        }
+    }
+}
+
+// finds the middle snake of the box `off1..lim1` times `off2..lim2`, like
+// `xdl_split`, with its heuristics for expensive alignments
+#[allow(clippy::too_many_arguments)]
+fn split(
+    ha1: &[usize],
+    off1: isize,
+    lim1: isize,
+    ha2: &[usize],
+    off2: isize,
+    lim2: isize,
+    kvdf: &mut Diagonals,
+    kvdb: &mut Diagonals,
+    need_min: bool,
+    mxcost: isize,
+) -> Split {
+    let eq = |i1: isize, i2: isize| ha1[i1 as usize] == ha2[i2 as usize];
+    let (dmin, dmax) = (off1 - lim2, lim1 - off2);
+    let (fmid, bmid) = (off1 - off2, lim1 - lim2);
+    let odd = (fmid - bmid) & 1 != 0;
+    let (mut fmin, mut fmax) = (fmid, fmid);
+    let (mut bmin, mut bmax) = (bmid, bmid);
+
+    kvdf.set(fmid, off1);
+    kvdb.set(bmid, lim1);
+
+    let mut ec = 1;
+    loop {
+        let mut got_snake = false;
+
+        if fmin > dmin {
+            fmin -= 1;
+            kvdf.set(fmin - 1, -1);
+        } else {
+            fmin += 1;
+        }
+        if fmax < dmax {
+            fmax += 1;
+            kvdf.set(fmax + 1, -1);
+        } else {
+            fmax -= 1;
+        }
+
+        let mut d = fmax;
+        while d >= fmin {
+            let mut i1 = if kvdf.get(d - 1) >= kvdf.get(d + 1) {
+                kvdf.get(d - 1) + 1
+            } else {
+                kvdf.get(d + 1)
+            };
+            let prev1 = i1;
+            let mut i2 = i1 - d;
+            while i1 < lim1 && i2 < lim2 && eq(i1, i2) {
+                i1 += 1;
+                i2 += 1;
+            }
+            if i1 - prev1 > SNAKE_CNT {
+                got_snake = true;
+            }
+            kvdf.set(d, i1);
+            if odd && bmin <= d && d <= bmax && kvdb.get(d) <= i1 {
+                return Split {
+                    i1,
+                    i2,
+                    min_lo: true,
+                    min_hi: true,
+                };
+            }
+            d -= 2;
+        }
+
+        if bmin > dmin {
+            bmin -= 1;
+            kvdb.set(bmin - 1, isize::MAX);
+        } else {
+            bmin += 1;
+        }
+        if bmax < dmax {
+            bmax += 1;
+            kvdb.set(bmax + 1, isize::MAX);
+        } else {
+            bmax -= 1;
+        }
+
+        let mut d = bmax;
+        while d >= bmin {
+            let mut i1 = if kvdb.get(d - 1) < kvdb.get(d + 1) {
+                kvdb.get(d - 1)
+            } else {
+                kvdb.get(d + 1) - 1
+            };
+            let prev1 = i1;
+            let mut i2 = i1 - d;
+            while i1 > off1 && i2 > off2 && eq(i1 - 1, i2 - 1) {
+                i1 -= 1;
+                i2 -= 1;
+            }
+            if prev1 - i1 > SNAKE_CNT {
+                got_snake = true;
+            }
+            kvdb.set(d, i1);
+            if !odd && fmin <= d && d <= fmax && i1 <= kvdf.get(d) {
+                return Split {
+                    i1,
+                    i2,
+                    min_lo: true,
+                    min_hi: true,
+                };
+            }
+            d -= 2;
+        }
+
+        if need_min {
+            ec += 1;
+            continue;
+        }
+
+        // with a good snake and a high cost, take a diagonal that has gone
+        // far enough, ending in a snake
+        if got_snake && ec > HEUR_MIN_COST {
+            let mut best = 0;
+            let mut spl = Split {
+                i1: 0,
+                i2: 0,
+                min_lo: true,
+                min_hi: false,
+            };
+            let mut d = fmax;
+            while d >= fmin {
+                let dd = if d > fmid { d - fmid } else { fmid - d };
+                let i1 = kvdf.get(d);
+                let i2 = i1 - d;
+                let v = (i1 - off1) + (i2 - off2) - dd;
+                if v > K_HEUR * ec
+                    && v > best
+                    && off1 + SNAKE_CNT <= i1
+                    && i1 < lim1
+                    && off2 + SNAKE_CNT <= i2
+                    && i2 < lim2
+                {
+                    let mut k = 1;
+                    while eq(i1 - k, i2 - k) {
+                        if k == SNAKE_CNT {
+                            best = v;
+                            spl.i1 = i1;
+                            spl.i2 = i2;
+                            break;
+                        }
+                        k += 1;
+                    }
+                }
+                d -= 2;
+            }
+            if best > 0 {
+                return spl;
+            }
+
+            let mut best = 0;
+            let mut spl = Split {
+                i1: 0,
+                i2: 0,
+                min_lo: false,
+                min_hi: true,
+            };
+            let mut d = bmax;
+            while d >= bmin {
+                let dd = if d > bmid { d - bmid } else { bmid - d };
+                let i1 = kvdb.get(d);
+                let i2 = i1 - d;
+                let v = (lim1 - i1) + (lim2 - i2) - dd;
+                if v > K_HEUR * ec
+                    && v > best
+                    && off1 < i1
+                    && i1 <= lim1 - SNAKE_CNT
+                    && off2 < i2
+                    && i2 <= lim2 - SNAKE_CNT
+                {
+                    let mut k = 0;
+                    while eq(i1 + k, i2 + k) {
+                        if k == SNAKE_CNT - 1 {
+                            best = v;
+                            spl.i1 = i1;
+                            spl.i2 = i2;
+                            break;
+                        }
+                        k += 1;
+                    }
+                }
+                d -= 2;
+            }
+            if best > 0 {
+                return spl;
+            }
+        }
+
+        // the cost is too high, take the furthest reaching path
+        if ec >= mxcost {
+            let (mut fbest, mut fbest1) = (-1, -1);
+            let mut d = fmax;
+            while d >= fmin {
+                let mut i1 = kvdf.get(d).min(lim1);
+                let mut i2 = i1 - d;
+                if lim2 < i2 {
+                    i1 = lim2 + d;
+                    i2 = lim2;
+                }
+                if fbest < i1 + i2 {
+                    fbest = i1 + i2;
+                    fbest1 = i1;
+                }
+                d -= 2;
+            }
+
+            let (mut bbest, mut bbest1) = (isize::MAX, isize::MAX);
+            let mut d = bmax;
+            while d >= bmin {
+                let mut i1 = kvdb.get(d).max(off1);
+                let mut i2 = i1 - d;
+                if i2 < off2 {
+                    i1 = off2 + d;
+                    i2 = off2;
+                }
+                if i1 + i2 < bbest {
+                    bbest = i1 + i2;
+                    bbest1 = i1;
+                }
+                d -= 2;
+            }
+
+            return if (lim1 + lim2) - bbest < fbest - (off1 + off2) {
+                Split {
+                    i1: fbest1,
+                    i2: fbest - fbest1,
+                    min_lo: true,
+                    min_hi: false,
+                }
+            } else {
+                Split {
+                    i1: bbest1,
+                    i2: bbest - bbest1,
+                    min_lo: false,
+                    min_hi: true,
+                }
+            };
+        }
+        ec += 1;
+    }
+}
+
+// a group of changed tokens `start..end`, which may be empty
+#[derive(Clone, Copy)]
+struct Group {
+    start: isize,
+    end: isize,
+}
+
+impl Group {
+    fn first(f: &File) -> Group {
+        let mut g = Group { start: 0, end: 0 };
+        while f.changed(g.end) {
+            g.end += 1;
+        }
+        g
+    }
+
+    // moves to the next group, returns false at the end of the sequence
+    fn next(&mut self, f: &File) -> bool {
+        if self.end == f.len() {
+            return false;
+        }
+        self.start = self.end + 1;
+        self.end = self.start;
+        while f.changed(self.end) {
+            self.end += 1;
+        }
+        true
+    }
+
+    // moves to the previous group, returns false at the start of the sequence
+    fn previous(&mut self, f: &File) -> bool {
+        if self.start == 0 {
+            return false;
+        }
+        self.end = self.start - 1;
+        self.start = self.end;
+        while f.changed(self.start - 1) {
+            self.start -= 1;
+        }
+        true
+    }
+
+    // moves the group down by one token, merging it with the group after it
+    fn slide_down(&mut self, f: &mut File) -> bool {
+        if self.end < f.len() && f.ha[self.start as usize] == f.ha[self.end as usize] {
+            f.set_changed(self.start, false);
+            f.set_changed(self.end, true);
+            self.start += 1;
+            self.end += 1;
+            while f.changed(self.end) {
+                self.end += 1;
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    // moves the group up by one token, merging it with the group before it
+    fn slide_up(&mut self, f: &mut File) -> bool {
+        if self.start > 0 && f.ha[self.start as usize - 1] == f.ha[self.end as usize - 1] {
+            self.start -= 1;
+            self.end -= 1;
+            f.set_changed(self.start, true);
+            f.set_changed(self.end, false);
+            while f.changed(self.start - 1) {
+                self.start -= 1;
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// slides the groups of changes of `f` down as far as possible, or up to the
+// last position where they line up with a change of `other`, or with the
+// indent heuristic, like `xdl_change_compact`
+fn change_compact(f: &mut File, other: &File, indent_heuristic: bool) {
+    let mut g = Group::first(f);
+    let mut go = Group::first(other);
+
+    loop {
+        if g.end != g.start {
+            let mut groupsize;
+            let mut earliest_end;
+            let mut end_matching_other;
+            loop {
+                groupsize = g.end - g.start;
+                end_matching_other = -1;
+
+                while g.slide_up(f) {
+                    go.previous(other);
+                }
+                earliest_end = g.end;
+                if go.end > go.start {
+                    end_matching_other = g.end;
+                }
+
+                while g.slide_down(f) {
+                    go.next(other);
+                    if go.end > go.start {
+                        end_matching_other = g.end;
+                    }
+                }
+                if groupsize == g.end - g.start {
+                    break;
+                }
+            }
+
+            if g.end == earliest_end {
+                // the group cannot be moved
+            } else if end_matching_other != -1 {
+                while go.end == go.start {
+                    g.slide_up(f);
+                    go.previous(other);
+                }
+            } else if indent_heuristic {
+                let mut shift = earliest_end.max(g.end - groupsize - 1).max(g.end - MAX_SLIDING);
+                let mut best: Option<(isize, Score)> = None;
+                while shift <= g.end {
+                    let mut score = Score::default();
+                    score.add(&measure_split(f, shift));
+                    score.add(&measure_split(f, shift - groupsize));
+                    if best.as_ref().is_none_or(|b| score.cmp(&b.1) <= 0) {
+                        best = Some((shift, score));
+                    }
+                    shift += 1;
+                }
+                let best_shift = best.map_or(g.end, |b| b.0);
+                while g.end > best_shift {
+                    g.slide_up(f);
+                    go.previous(other);
+                }
+            }
+        }
+
+        if !g.next(f) {
+            break;
+        }
+        go.next(other);
+    }
+}
+
+// the limits of the indent heuristic of git
+const MAX_INDENT: isize = 200;
+const MAX_BLANKS: isize = 20;
+const MAX_SLIDING: isize = 100;
+
+// the penalties and weights of the indent heuristic of git
+const START_OF_FILE_PENALTY: isize = 1;
+const END_OF_FILE_PENALTY: isize = 21;
+const TOTAL_BLANK_WEIGHT: isize = -30;
+const POST_BLANK_WEIGHT: isize = 6;
+const RELATIVE_INDENT_PENALTY: isize = -4;
+const RELATIVE_INDENT_WITH_BLANK_PENALTY: isize = 10;
+const RELATIVE_OUTDENT_PENALTY: isize = 24;
+const RELATIVE_OUTDENT_WITH_BLANK_PENALTY: isize = 17;
+const RELATIVE_DEDENT_PENALTY: isize = 23;
+const RELATIVE_DEDENT_WITH_BLANK_PENALTY: isize = 17;
+const INDENT_WEIGHT: isize = 60;
+
+// the surroundings of a split between two lines, before line `split`
+struct Measurement {
+    end_of_file: bool,
+    indent: isize,
+    pre_blank: isize,
+    pre_indent: isize,
+    post_blank: isize,
+    post_indent: isize,
+}
+
+// the indentation of a line in columns, or -1 if it is blank
+fn get_indent(line: &str) -> isize {
+    let mut ret = 0;
+    for &c in line.as_bytes() {
+        if !is_space(c) {
+            return ret;
+        } else if c == b' ' {
+            ret += 1;
+        } else if c == b'\t' {
+            ret += 8 - ret % 8;
+        }
+        if ret >= MAX_INDENT {
+            return MAX_INDENT;
+        }
+    }
+    -1
+}
+
+// whether a byte is whitespace as defined by git
+pub fn is_space(c: u8) -> bool {
+    c == b' ' || c == b'\t' || c == b'\n' || c == b'\r'
+}
+
+fn measure_split(f: &File, split: isize) -> Measurement {
+    let (end_of_file, indent) = if split >= f.len() {
+        (true, -1)
+    } else {
+        (false, get_indent(f.recs[split as usize]))
+    };
+
+    let (mut pre_blank, mut pre_indent) = (0, -1);
+    let mut i = split - 1;
+    while i >= 0 {
+        pre_indent = get_indent(f.recs[i as usize]);
+        if pre_indent != -1 {
+            break;
+        }
+        pre_blank += 1;
+        if pre_blank == MAX_BLANKS {
+            pre_indent = 0;
+            break;
+        }
+        i -= 1;
+    }
+
+    let (mut post_blank, mut post_indent) = (0, -1);
+    let mut i = split + 1;
+    while i < f.len() {
+        post_indent = get_indent(f.recs[i as usize]);
+        if post_indent != -1 {
+            break;
+        }
+        post_blank += 1;
+        if post_blank == MAX_BLANKS {
+            post_indent = 0;
+            break;
+        }
+        i += 1;
+    }
+
+    Measurement {
+        end_of_file,
+        indent,
+        pre_blank,
+        pre_indent,
+        post_blank,
+        post_indent,
+    }
+}
+
+// the badness of the position of a group, lower is better
+#[derive(Clone, Copy, Default)]
+struct Score {
+    effective_indent: isize,
+    penalty: isize,
+}
+
+impl Score {
+    fn add(&mut self, m: &Measurement) {
+        if m.pre_indent == -1 && m.pre_blank == 0 {
+            self.penalty += START_OF_FILE_PENALTY;
+        }
+        if m.end_of_file {
+            self.penalty += END_OF_FILE_PENALTY;
+        }
+
+        let post_blank = if m.indent == -1 { 1 + m.post_blank } else { 0 };
+        let total_blank = m.pre_blank + post_blank;
+        self.penalty += TOTAL_BLANK_WEIGHT * total_blank;
+        self.penalty += POST_BLANK_WEIGHT * post_blank;
+
+        let indent = if m.indent != -1 { m.indent } else { m.post_indent };
+        let any_blanks = total_blank != 0;
+        self.effective_indent += indent;
+
+        if indent == -1 || m.pre_indent == -1 || indent == m.pre_indent {
+            // no adjustments
+        } else if indent > m.pre_indent {
+            self.penalty += if any_blanks {
+                RELATIVE_INDENT_WITH_BLANK_PENALTY
+            } else {
+                RELATIVE_INDENT_PENALTY
+            };
+        } else if m.post_indent != -1 && m.post_indent > indent {
+            self.penalty += if any_blanks {
+                RELATIVE_OUTDENT_WITH_BLANK_PENALTY
+            } else {
+                RELATIVE_OUTDENT_PENALTY
+            };
+        } else {
+            self.penalty += if any_blanks {
+                RELATIVE_DEDENT_WITH_BLANK_PENALTY
+            } else {
+                RELATIVE_DEDENT_PENALTY
+            };
+        }
+    }
+
+    // negative if `self` is better than `other`
+    fn cmp(&self, other: &Score) -> isize {
+        let indents = (self.effective_indent > other.effective_indent) as isize
+            - (self.effective_indent < other.effective_indent) as isize;
+        INDENT_WEIGHT * indents + (self.penalty - other.penalty)
+    }
+}
@@ -1,7 +1,7 @@
 extern crate difference;
 extern crate quickcheck;
 
-use difference::{Changeset, Difference};
+use difference::{distance, distance_within, Changeset, Difference};
 use quickcheck::{TestResult, quickcheck, QuickCheck};
 use std::fmt;
 
@@ -41,8 +41,8 @@ fn check_changeset(old: &str, new: &str, split: &str) -> TestResult {
 impl<'a> Check<'a> {
     fn new(old: &'a str, new: &'a str, split: &'a str) -> Check<'a> {
         Check {
-            old: old,
-            new: new,
+            old,
+            new,
             changeset: Changeset::new(old, new, split),
         }
     }
@@ -84,6 +84,15 @@ impl<'a> Check<'a> {
                 ));
         }
 
+        let stats = self.changeset.stats();
+        let dist = self.changeset.edit_distance();
+        if stats.added + stats.removed != dist || self.changeset.distance as usize != dist {
+            return TestResult::error(format!("Stats count {} changed tokens and the distance is {}, \
+                        not the edit distance {} in {}",
+                        stats.added + stats.removed, self.changeset.distance, dist, self,
+                ));
+        }
+
         TestResult::passed()
     }
 }
@@ -100,7 +109,7 @@ fn issue_19() {
 }
 
 #[test]
-#[allow(needless_pass_by_value)]
+#[allow(clippy::needless_pass_by_value)]
 fn fuzzy() {
     fn prop(old: Vec<usize>, new: Vec<usize>, words: Vec<char>) -> TestResult {
         if words.is_empty() {
@@ -130,3 +139,18 @@ fn fuzzy() {
         .max_tests(10_000) // max attempts
         .quickcheck(prop as fn(Vec<usize>, Vec<usize>, Vec<char>) -> TestResult);
 }
+
+#[test]
+fn fuzzy_distance() {
+    fn prop(old: Vec<u8>, new: Vec<u8>, k: u8) -> bool {
+        let old: String = old.iter().map(|x| (b'a' + x % 4) as char).collect();
+        let new: String = new.iter().map(|x| (b'a' + x % 4) as char).collect();
+
+        let expected = Changeset::new(&old, &new, "").edit_distance();
+        let k = k as usize % 8;
+        distance(&old, &new, "") == expected &&
+            distance_within(&old, &new, "", k) == if expected <= k { Some(expected) } else { None }
+    }
+
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>, u8) -> bool);
+}